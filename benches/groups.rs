@@ -0,0 +1,54 @@
+use ray_tracer::{
+    camera::Camera,
+    color,
+    groups::Group,
+    lights::{Light, PointLight},
+    point,
+    shapes::Shape,
+    spheres::Sphere,
+    transformations::{scaling, translation, view_transform},
+    vector,
+    world::World,
+};
+use std::{f64::consts::PI, hint::black_box, time::Instant};
+
+fn build_world(threshold: Option<usize>) -> World {
+    let mut group = Group::new();
+
+    for i in 0..400 {
+        let (x, z) = ((i % 20) as f64 - 9.5, (i / 20) as f64 - 9.5);
+        let mut sphere = Sphere::new();
+        sphere.set_transform(translation(x * 0.5, 0.0, z * 0.5) * scaling(0.2, 0.2, 0.2));
+        group.add_child(Box::new(sphere));
+    }
+
+    if let Some(threshold) = threshold {
+        group.divide(threshold);
+    }
+
+    let mut world = World::new();
+    world.objects.push(Box::new(group));
+    world.lights.push(Light::from(PointLight::new(
+        point!(-10, 10, -10),
+        color!(1, 1, 1),
+    )));
+    world
+}
+
+fn main() {
+    let mut camera = Camera::new(200, 200, PI / 3.0);
+    camera.set_transform(view_transform(
+        point!(0, 6, -8),
+        point!(0, 0, 0),
+        vector!(0, 1, 0),
+    ));
+
+    for (name, threshold) in [("group undivided", None), ("group divided", Some(4))] {
+        let world = build_world(threshold);
+
+        let start = Instant::now();
+        black_box(camera.render(black_box(&world)));
+
+        println!("{name:<24} {:>10.2?}", start.elapsed());
+    }
+}
@@ -0,0 +1,46 @@
+use ray_tracer::{
+    matrices::Matrix,
+    transformations::{rotation, scaling, translation, Axis},
+};
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+const ITERATIONS: u32 = 100_000;
+
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+
+    let elapsed = start.elapsed();
+    let per_iter = elapsed / ITERATIONS;
+
+    println!(
+        "{name:<24} {:>10.2?} total {:>10?}/iter",
+        elapsed,
+        Duration::from_nanos(per_iter.as_nanos() as u64)
+    );
+}
+
+fn main() {
+    let matrix = Matrix::new([
+        [8.0, -5.0, 9.0, 2.0],
+        [7.0, 5.0, 6.0, 1.0],
+        [-6.0, 0.0, 9.0, 6.0],
+        [-3.0, 0.0, -9.0, -4.0],
+    ]);
+
+    bench("Matrix<4>::inverse", || black_box(matrix).inverse());
+
+    let affine = translation(1.0, 2.0, 3.0) * rotation(Axis::Y, 1.0) * scaling(2.0, 2.0, 0.5);
+
+    bench("Matrix<4>::inverse affine", || black_box(affine).inverse());
+
+    let matrix = Matrix::new([[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]]);
+
+    bench("Matrix<3>::inverse", || black_box(matrix).inverse());
+}
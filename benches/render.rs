@@ -0,0 +1,167 @@
+use ray_tracer::{
+    bounds::AABB,
+    camera::Camera,
+    intersections::{Intersection, Intersections},
+    materials::Material,
+    matrices::Matrix,
+    point,
+    rays::Ray,
+    shapes::Shape,
+    spheres::Sphere,
+    transformations::view_transform,
+    tuples::Tuple,
+    vector,
+    world::World,
+};
+use std::{any::Any, f64::consts::PI, hint::black_box, time::Instant};
+
+type Invert = fn(&Matrix<4>) -> Option<Matrix<4>>;
+
+// a sphere that inverts its transform on every intersect and normal_at instead of using the
+// cached inverse, so the inversion strategy shows up in the render time
+#[derive(Debug)]
+struct PerRay {
+    sphere: Sphere,
+    invert: Invert,
+}
+
+impl Shape for PerRay {
+    fn transform(&self) -> &Matrix<4> {
+        self.sphere.transform()
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.sphere.set_transform(transform);
+    }
+
+    fn inverse(&self) -> Option<Matrix<4>> {
+        (self.invert)(self.sphere.transform())
+    }
+
+    fn inverse_transpose(&self) -> Option<Matrix<4>> {
+        self.inverse().map(|inverse| inverse.transpose())
+    }
+
+    fn material(&self) -> &Material {
+        self.sphere.material()
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        self.sphere.material_mut()
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let xs = self.sphere.local_intersect(ray);
+
+        Intersections(
+            xs.into_iter()
+                .map(|intersection| Intersection {
+                    object: self,
+                    ..intersection
+                })
+                .collect(),
+        )
+    }
+
+    fn local_normal_at(&self, point: &Tuple) -> Tuple {
+        self.sphere.local_normal_at(point)
+    }
+
+    fn bounds(&self) -> AABB {
+        self.sphere.bounds()
+    }
+}
+
+// the cofactor expansion Matrix<4>::inverse used before the LU path
+fn cofactor_inverse(matrix: &Matrix<4>) -> Option<Matrix<4>> {
+    let cofactor = |row: usize, col: usize| {
+        let mut submatrix = [[0.0; 3]; 3];
+
+        for (i, r) in (0..4).filter(|&r| r != row).enumerate() {
+            for (j, c) in (0..4).filter(|&c| c != col).enumerate() {
+                submatrix[i][j] = matrix[r][c];
+            }
+        }
+
+        let minor = Matrix::new(submatrix).determinant();
+        match (row + col) % 2 {
+            1 => -minor,
+            _ => minor,
+        }
+    };
+
+    let determinant: f64 = (0..4).map(|col| matrix[0][col] * cofactor(0, col)).sum();
+    if determinant == 0.0 {
+        return None;
+    }
+
+    let mut output = [[0.0; 4]; 4];
+    for (row, values) in output.iter_mut().enumerate() {
+        for (col, value) in values.iter_mut().enumerate() {
+            *value = cofactor(col, row) / determinant;
+        }
+    }
+
+    Some(Matrix::new(output))
+}
+
+fn per_ray_world(invert: Invert) -> World {
+    let mut world = World::default();
+
+    world.objects = world
+        .objects
+        .into_iter()
+        .map(|object| {
+            let sphere = (object.as_ref() as &dyn Any)
+                .downcast_ref::<Sphere>()
+                .unwrap()
+                .clone();
+
+            Box::new(PerRay { sphere, invert }) as Box<dyn Shape>
+        })
+        .collect();
+
+    world
+}
+
+fn time(name: &str, f: impl FnOnce()) {
+    let start = Instant::now();
+    f();
+
+    println!("{name:<32} {:>10.2?}", start.elapsed());
+}
+
+fn main() {
+    let world = World::default();
+    let mut camera = Camera::new(500, 500, PI / 3.0);
+    camera.set_transform(view_transform(
+        point!(0, 1.5, -5),
+        point!(0, 0, 0),
+        vector!(0, 1, 0),
+    ));
+
+    time("render 500x500", || {
+        black_box(camera.render(black_box(&world)));
+    });
+
+    time("render_parallel 500x500", || {
+        black_box(camera.render_parallel(black_box(&world)));
+    });
+
+    // the default world's transforms are all affine, so Matrix::inverse would take the 3x3 fast
+    // path; calling each strategy directly times the inversion a shape pays per ray without
+    // its cached inverse
+    let strategies: [(&str, Invert); 3] = [
+        ("per-ray cofactor 500x500", cofactor_inverse),
+        ("per-ray lu_inverse 500x500", Matrix::lu_inverse),
+        ("per-ray inverse 500x500", Matrix::<4>::inverse),
+    ];
+
+    for (name, invert) in strategies {
+        let world = per_ray_world(invert);
+
+        time(name, || {
+            black_box(camera.render(black_box(&world)));
+        });
+    }
+}
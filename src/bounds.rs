@@ -0,0 +1,250 @@
+use crate::{constants::EPSILON, matrices::Matrix, rays::Ray, tuples::Tuple};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AABB {
+    pub min: Tuple,
+    pub max: Tuple,
+}
+
+impl AABB {
+    pub const fn new(min: Tuple, max: Tuple) -> Self {
+        Self { min, max }
+    }
+
+    // inverted so that adding the first point or box snaps to it
+    pub const fn empty() -> Self {
+        Self {
+            min: point!(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: point!(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    pub fn add_point(&mut self, point: &Tuple) {
+        self.min = point!(
+            self.min.0.min(point.0),
+            self.min.1.min(point.1),
+            self.min.2.min(point.2)
+        );
+        self.max = point!(
+            self.max.0.max(point.0),
+            self.max.1.max(point.1),
+            self.max.2.max(point.2)
+        );
+    }
+
+    pub fn add_box(&mut self, other: &AABB) {
+        self.add_point(&other.min);
+        self.add_point(&other.max);
+    }
+
+    // false for unbounded shapes such as planes
+    pub fn is_finite(&self) -> bool {
+        [self.min, self.max]
+            .iter()
+            .all(|corner| corner.0.is_finite() && corner.1.is_finite() && corner.2.is_finite())
+    }
+
+    pub fn contains_point(&self, point: &Tuple) -> bool {
+        (self.min.0..=self.max.0).contains(&point.0)
+            && (self.min.1..=self.max.1).contains(&point.1)
+            && (self.min.2..=self.max.2).contains(&point.2)
+    }
+
+    pub fn contains_box(&self, other: &AABB) -> bool {
+        self.contains_point(&other.min) && self.contains_point(&other.max)
+    }
+
+    // the smallest axis-aligned box around all eight transformed corners
+    pub fn transform(&self, matrix: &Matrix<4>) -> AABB {
+        let mut output = AABB::empty();
+
+        for x in [self.min.0, self.max.0] {
+            for y in [self.min.1, self.max.1] {
+                for z in [self.min.2, self.max.2] {
+                    output.add_point(&transform_corner(matrix, [x, y, z]));
+                }
+            }
+        }
+
+        output
+    }
+
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        if self.min.0 > self.max.0 {
+            return false;
+        }
+
+        let (xtmin, xtmax) = check_axis(ray.origin.0, ray.direction.0, self.min.0, self.max.0);
+        let (ytmin, ytmax) = check_axis(ray.origin.1, ray.direction.1, self.min.1, self.max.1);
+        let (ztmin, ztmax) = check_axis(ray.origin.2, ray.direction.2, self.min.2, self.max.2);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        tmin <= tmax
+    }
+}
+
+impl Default for AABB {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+// skips zero entries so an infinite extent doesn't turn into 0 * inf = NaN
+fn transform_corner(matrix: &Matrix<4>, corner: [f64; 3]) -> Tuple {
+    let [x, y, z] = [0, 1, 2].map(|row| {
+        let entries = matrix[row];
+
+        (0..3)
+            .filter(|&i| entries[i] != 0.0)
+            .map(|i| entries[i] * corner[i])
+            .sum::<f64>()
+            + entries[3]
+    });
+
+    point!(x, y, z)
+}
+
+// the same slab test as Cube, but between arbitrary planes
+fn check_axis(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
+    let tmin_numerator = min - origin;
+    let tmax_numerator = max - origin;
+
+    let (tmin, tmax) = if direction.abs() >= EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (
+            tmin_numerator * f64::INFINITY,
+            tmax_numerator * f64::INFINITY,
+        )
+    };
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{rotate_x, rotate_y, rotate_z, scale, translate};
+    use std::f64::consts::{PI, SQRT_2};
+
+    #[test]
+    fn test_add_point() {
+        let mut aabb = AABB::empty();
+        aabb.add_point(&point!(-5, 2, 0));
+        aabb.add_point(&point!(7, 0, -3));
+
+        assert_eq!(point!(-5, 0, -3), aabb.min);
+        assert_eq!(point!(7, 2, 0), aabb.max);
+    }
+
+    #[test]
+    fn test_add_box() {
+        let mut aabb = AABB::new(point!(-5, -2, 0), point!(7, 4, 4));
+        aabb.add_box(&AABB::new(point!(8, -7, -2), point!(14, 2, 8)));
+
+        assert_eq!(point!(-5, -7, -2), aabb.min);
+        assert_eq!(point!(14, 4, 8), aabb.max);
+    }
+
+    #[test]
+    fn test_contains() {
+        let aabb = AABB::new(point!(5, -2, 0), point!(11, 4, 7));
+
+        let points = [
+            (point!(5, -2, 0), true),
+            (point!(11, 4, 7), true),
+            (point!(8, 1, 3), true),
+            (point!(3, 0, 3), false),
+            (point!(8, -4, 3), false),
+            (point!(8, 1, -1), false),
+            (point!(13, 1, 3), false),
+            (point!(8, 5, 3), false),
+            (point!(8, 1, 8), false),
+        ];
+
+        for (point, expected) in points {
+            assert_eq!(expected, aabb.contains_point(&point), "{point:?}");
+        }
+
+        let boxes = [
+            (point!(5, -2, 0), point!(11, 4, 7), true),
+            (point!(6, -1, 1), point!(10, 3, 6), true),
+            (point!(4, -3, -1), point!(10, 3, 6), false),
+            (point!(6, -1, 1), point!(12, 5, 8), false),
+        ];
+
+        for (min, max, expected) in boxes {
+            assert_eq!(expected, aabb.contains_box(&AABB::new(min, max)));
+        }
+    }
+
+    #[test]
+    fn test_transform() {
+        let aabb = AABB::new(point!(-1, -1, -1), point!(1, 1, 1));
+
+        let rotated = aabb.transform(&rotate_y!(PI / 4.0));
+        assert_eq!(point!(-SQRT_2, -1, -SQRT_2), rotated.min);
+        assert_eq!(point!(SQRT_2, 1, SQRT_2), rotated.max);
+
+        let scaled = aabb.transform(&(scale!(2, 3, 4) * rotate_y!(PI / 4.0)));
+        assert_eq!(point!(-2.0 * SQRT_2, -3, -4.0 * SQRT_2), scaled.min);
+        assert_eq!(point!(2.0 * SQRT_2, 3, 4.0 * SQRT_2), scaled.max);
+
+        let transformed = aabb.transform(&(rotate_x!(PI / 4.0) * rotate_y!(PI / 4.0)));
+        assert_eq!(point!(-SQRT_2, -1.70711, -1.70711), transformed.min);
+        assert_eq!(point!(SQRT_2, 1.70711, 1.70711), transformed.max);
+    }
+
+    #[test]
+    fn test_transform_infinite() {
+        let plane = AABB::new(
+            point!(f64::NEG_INFINITY, 0, f64::NEG_INFINITY),
+            point!(f64::INFINITY, 0, f64::INFINITY),
+        );
+
+        let moved = plane.transform(&translate!(0, 2, 0));
+        assert_eq!(f64::NEG_INFINITY, moved.min.0);
+        assert_eq!(2.0, moved.min.1);
+        assert_eq!(2.0, moved.max.1);
+        assert_eq!(f64::INFINITY, moved.max.2);
+
+        let tilted = plane.transform(&rotate_z!(PI / 4.0));
+        assert_eq!(f64::NEG_INFINITY, tilted.min.1);
+        assert_eq!(f64::INFINITY, tilted.max.1);
+    }
+
+    #[test]
+    fn test_intersects() {
+        let aabb = AABB::new(point!(5, -2, 0), point!(11, 4, 7));
+
+        let cases = [
+            (point!(15, 1, 2), vector!(-1, 0, 0), true),
+            (point!(-5, -1, 4), vector!(1, 0, 0), true),
+            (point!(7, 6, 5), vector!(0, -1, 0), true),
+            (point!(9, -5, 6), vector!(0, 1, 0), true),
+            (point!(8, 2, 12), vector!(0, 0, -1), true),
+            (point!(6, 0, -5), vector!(0, 0, 1), true),
+            (point!(8, 1, 3.5), vector!(0, 0, 1), true),
+            (point!(9, -1, -8), vector!(2, 4, 6), false),
+            (point!(8, 3, -4), vector!(6, 2, 4), false),
+            (point!(9, -1, -2), vector!(4, 6, 2), false),
+            (point!(4, 0, 9), vector!(0, 0, -1), false),
+            (point!(8, 6, -1), vector!(0, -1, 0), false),
+            (point!(12, 5, 4), vector!(-1, 0, 0), false),
+        ];
+
+        for (origin, direction, expected) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(expected, aabb.intersects(&ray), "{origin:?}");
+        }
+
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+        assert!(!AABB::empty().intersects(&ray));
+    }
+}
@@ -0,0 +1,893 @@
+use crate::{
+    bounds::AABB,
+    canvas::{Canvas, Ppm, ToneMap},
+    constants::EPSILON,
+    lights::jitter_offset,
+    matrices::Matrix,
+    rays::Ray,
+    shapes::Shape,
+    tuples::Tuple,
+    world::World,
+};
+use std::{
+    f64::consts::PI,
+    io::{self, Write},
+    num::NonZeroUsize,
+    ptr, thread,
+};
+
+const WIREFRAME_NORMAL_THRESHOLD: f64 = 0.9;
+const WIREFRAME_DEPTH_THRESHOLD: f64 = 0.1;
+const LENS_SAMPLES: usize = 16;
+
+struct Sample<'a> {
+    object: &'a dyn Shape,
+    depth: f64,
+    normal: Tuple,
+}
+
+impl Sample<'_> {
+    fn is_discontinuous(&self, other: &Self) -> bool {
+        !ptr::addr_eq(self.object, other.object)
+            || self.normal.dot_product(other.normal) < WIREFRAME_NORMAL_THRESHOLD
+            || (self.depth - other.depth).abs()
+                > WIREFRAME_DEPTH_THRESHOLD * self.depth.min(other.depth)
+    }
+}
+
+pub struct Camera {
+    hsize: usize,
+    vsize: usize,
+    field_of_view: f64,
+    transform: Matrix<4>,
+    // cached for casting rays; set_transform only accepts invertible transforms
+    inverse: Matrix<4>,
+    half_width: f64,
+    half_height: f64,
+    pixel_size: f64,
+    squeeze: f64,
+    // the lens diameter; 0 is a pinhole camera with everything in focus
+    aperture: f64,
+    focal_distance: f64,
+}
+
+// half_width, half_height and pixel_size are rebuilt by Camera::new when loading
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CameraData {
+    hsize: usize,
+    vsize: usize,
+    field_of_view: f64,
+    transform: Matrix<4>,
+    squeeze: f64,
+    aperture: f64,
+    focal_distance: f64,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Camera {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        CameraData {
+            hsize: self.hsize,
+            vsize: self.vsize,
+            field_of_view: self.field_of_view,
+            transform: self.transform,
+            squeeze: self.squeeze,
+            aperture: self.aperture,
+            focal_distance: self.focal_distance,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Camera {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = CameraData::deserialize(deserializer)?;
+        let inverse = data
+            .transform
+            .inverse()
+            .ok_or_else(|| serde::de::Error::custom("camera transform is singular"))?;
+
+        Ok(Camera {
+            transform: data.transform,
+            inverse,
+            squeeze: data.squeeze,
+            aperture: data.aperture,
+            focal_distance: data.focal_distance,
+            ..Camera::new(data.hsize, data.vsize, data.field_of_view)
+        })
+    }
+}
+
+impl Camera {
+    pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+        let half_view = (field_of_view / 2.0).tan();
+        let aspect = hsize as f64 / vsize as f64;
+
+        let (half_width, half_height) = if aspect >= 1.0 {
+            (half_view, half_view / aspect)
+        } else {
+            (half_view * aspect, half_view)
+        };
+
+        Self {
+            hsize,
+            vsize,
+            field_of_view,
+            transform: Matrix::identity(),
+            inverse: Matrix::identity(),
+            half_width,
+            half_height,
+            pixel_size: half_width * 2.0 / hsize as f64,
+            squeeze: 1.0,
+            aperture: 0.0,
+            focal_distance: 1.0,
+        }
+    }
+
+    pub fn hsize(&self) -> usize {
+        self.hsize
+    }
+
+    pub fn vsize(&self) -> usize {
+        self.vsize
+    }
+
+    pub fn field_of_view(&self) -> f64 {
+        self.field_of_view
+    }
+
+    // rays are cast through the inverse, so a singular view (say, up along the line of sight) is
+    // rejected here rather than panicking partway through a render
+    pub fn set_transform(&mut self, transform: Matrix<4>) {
+        self.inverse = transform.inverse().expect("camera transform is singular");
+        self.transform = transform;
+    }
+
+    pub fn squeeze(&self) -> f64 {
+        self.squeeze
+    }
+
+    // widens the horizontal field of view by this factor without changing hsize
+    pub fn set_squeeze(&mut self, squeeze: f64) {
+        self.squeeze = squeeze;
+    }
+
+    pub fn aperture(&self) -> f64 {
+        self.aperture
+    }
+
+    pub fn set_aperture(&mut self, aperture: f64) {
+        self.aperture = aperture;
+    }
+
+    pub fn focal_distance(&self) -> f64 {
+        self.focal_distance
+    }
+
+    // objects this far in front of the camera stay sharp when aperture > 0
+    pub fn set_focal_distance(&mut self, focal_distance: f64) {
+        self.focal_distance = focal_distance;
+    }
+
+    pub fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
+        self.ray_for_pixel_offset(px, py, 0.5, 0.5)
+    }
+
+    // dx and dy are measured from the pixel's top left corner, in pixels; with an
+    // aperture each offset also picks its own fixed point on the lens
+    pub fn ray_for_pixel_offset(&self, px: usize, py: usize, dx: f64, dy: f64) -> Ray {
+        let subpixel = |p: usize, d: f64| (p << 16) + (d * 65536.0) as usize;
+        let (u, v) = (subpixel(px, dx), subpixel(py, dy));
+        let radius = jitter_offset(u, v, 2).sqrt();
+        let theta = 2.0 * PI * jitter_offset(u, v, 3);
+
+        self.ray_through_lens(px, py, dx, dy, (radius * theta.cos(), radius * theta.sin()))
+    }
+
+    // lens is a point on the unit disk, scaled to the aperture
+    fn ray_through_lens(&self, px: usize, py: usize, dx: f64, dy: f64, lens: (f64, f64)) -> Ray {
+        let xoffset = (px as f64 + dx) * self.pixel_size;
+        let yoffset = (py as f64 + dy) * self.pixel_size;
+
+        let world_x = (self.half_width - xoffset) * self.squeeze;
+        let world_y = self.half_height - yoffset;
+
+        let inverse = self.inverse;
+
+        if self.aperture <= 0.0 {
+            let pixel = inverse * point!(world_x, world_y, -1);
+            let origin = inverse * point!(0, 0, 0);
+
+            return Ray::new(origin, (pixel - origin).normalize());
+        }
+
+        // start somewhere on the lens and aim through the pixel's spot on the focal plane
+        let radius = self.aperture / 2.0;
+        let focus = inverse
+            * point!(
+                world_x * self.focal_distance,
+                world_y * self.focal_distance,
+                -self.focal_distance
+            );
+        let origin = inverse * point!(lens.0 * radius, lens.1 * radius, 0);
+
+        Ray::new(origin, (focus - origin).normalize())
+    }
+
+    // a single ray for a pinhole, otherwise the average over a spiral of lens samples
+    fn pixel_color(&self, world: &World, x: usize, y: usize) -> Tuple {
+        if self.aperture <= 0.0 {
+            return world.color_at(&self.ray_for_pixel(x, y));
+        }
+
+        let golden_angle = PI * (3.0 - 5f64.sqrt());
+
+        let total = (0..LENS_SAMPLES).fold(color!(0, 0, 0), |acc, i| {
+            let radius = ((i as f64 + 0.5) / LENS_SAMPLES as f64).sqrt();
+            let theta = i as f64 * golden_angle;
+            let lens = (radius * theta.cos(), radius * theta.sin());
+
+            acc + world.color_at(&self.ray_through_lens(x, y, 0.5, 0.5, lens))
+        });
+
+        total / LENS_SAMPLES as f64
+    }
+
+    // conservative: false only when the box lies wholly behind the camera or past one edge of
+    // the view, so anything partly in frame (or unbounded) counts as seen
+    pub fn sees(&self, bounds: &AABB) -> bool {
+        let local = bounds.transform(&self.transform);
+        // how far the view reaches sideways per unit of depth
+        let (across, up) = (self.half_width * self.squeeze, self.half_height);
+
+        let outside = local.min.2 >= 0.0
+            || local.min.0 + across * local.min.2 > 0.0
+            || -local.max.0 + across * local.min.2 > 0.0
+            || local.min.1 + up * local.min.2 > 0.0
+            || -local.max.1 + up * local.min.2 > 0.0;
+
+        !outside
+    }
+
+    pub fn pixel_for_point(&self, point: &Tuple) -> Option<(f64, f64)> {
+        let local = self.transform * *point;
+
+        if local.2 > -EPSILON {
+            return None;
+        }
+
+        let world_x = local.0 / -local.2 / self.squeeze;
+        let world_y = local.1 / -local.2;
+
+        Some((
+            (self.half_width - world_x) / self.pixel_size - 0.5,
+            (self.half_height - world_y) / self.pixel_size - 0.5,
+        ))
+    }
+
+    pub fn render(&self, world: &World) -> Canvas {
+        self.render_with_progress(world, |_, _| {})
+    }
+
+    // calls progress with (rows_done, total_rows) after every finished row
+    pub fn render_with_progress(
+        &self,
+        world: &World,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                image.write_pixel(x, y, &self.pixel_color(world, x, y));
+            }
+
+            progress(y + 1, self.vsize);
+        }
+
+        image
+    }
+
+    // writes each row as soon as it's rendered instead of keeping the whole canvas
+    pub fn render_to_ppm<W: Write>(&self, world: &World, writer: &mut W) -> Result<(), io::Error> {
+        let mut output = Ppm::new(self.hsize, self.vsize, writer)?;
+
+        for y in 0..self.vsize {
+            let row = (0..self.hsize).map(|x| self.pixel_color(world, x, y).as_color());
+            output.write_row(row, ToneMap::None)?;
+        }
+
+        Ok(())
+    }
+
+    // splits the rows into one band per thread; identical to render
+    pub fn render_parallel(&self, world: &World) -> Canvas {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let band = self.vsize.div_ceil(threads).max(1);
+
+        let colors: Vec<Tuple> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..self.vsize)
+                .step_by(band)
+                .map(|start| {
+                    let end = (start + band).min(self.vsize);
+
+                    scope.spawn(move || {
+                        (start..end)
+                            .flat_map(|y| (0..self.hsize).map(move |x| (x, y)))
+                            .map(|(x, y)| self.pixel_color(world, x, y))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let mut image = Canvas::new(self.hsize, self.vsize);
+
+        for (i, color) in colors.iter().enumerate() {
+            image.write_pixel(i % self.hsize, i / self.hsize, color);
+        }
+
+        image
+    }
+
+    // averages a samples * samples grid of rays, each jittered within its cell and,
+    // with an aperture, through its own point on the lens
+    pub fn render_antialiased(&self, world: &World, samples: usize) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                image.write_pixel(x, y, &self.antialiased_color(world, x, y, samples));
+            }
+        }
+
+        image
+    }
+
+    fn antialiased_color(&self, world: &World, x: usize, y: usize, samples: usize) -> Tuple {
+        let n = samples.max(1);
+        let mut total = color!(0, 0, 0);
+
+        for j in 0..n {
+            for i in 0..n {
+                let (u, v) = (x * n + i, y * n + j);
+                let dx = (i as f64 + jitter_offset(u, v, 0)) / n as f64;
+                let dy = (j as f64 + jitter_offset(u, v, 1)) / n as f64;
+
+                total = total + world.color_at(&self.ray_for_pixel_offset(x, y, dx, dy));
+            }
+        }
+
+        total / (n * n) as f64
+    }
+
+    // each pixel stores how far its hit point has moved on screen since the previous frame, in
+    // pixels. prev_transforms holds where each of world.objects was then, by index; objects past
+    // the end of it are taken to have stayed put, so only the camera moved them
+    pub fn render_motion_vectors(
+        &self,
+        world: &World,
+        prev_camera: &Camera,
+        prev_transforms: &[Matrix<4>],
+    ) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                let ray = self.ray_for_pixel(x, y);
+                let intersections = world.intersect(&ray);
+
+                let Some(hit) = intersections.hit() else {
+                    continue;
+                };
+                let point = ray.position(hit.t);
+
+                // carry the point back along with whichever top-level object it belongs to
+                let moved = world
+                    .objects
+                    .iter()
+                    .zip(prev_transforms)
+                    .find(|(object, _)| object.includes(hit.object))
+                    .and_then(|(object, prev)| Some(*prev * object.inverse()? * point));
+                let previous = moved.unwrap_or(point);
+
+                if let Some((prev_x, prev_y)) = prev_camera.pixel_for_point(&previous) {
+                    image.write_pixel(x, y, &color!(x as f64 - prev_x, y as f64 - prev_y, 0));
+                }
+            }
+        }
+
+        image
+    }
+
+    fn sample<'a>(&self, world: &'a World, x: usize, y: usize) -> Option<Sample<'a>> {
+        let ray = self.ray_for_pixel(x, y);
+        let intersections = world.intersect(&ray);
+        let hit = intersections.hit()?;
+
+        Some(Sample {
+            object: hit.object,
+            depth: hit.t,
+            normal: hit.object.normal_at(&ray.position(hit.t)),
+        })
+    }
+
+    pub fn render_wireframe(&self, world: &World, line_color: &Tuple) -> Canvas {
+        let samples: Vec<Vec<_>> = (0..self.vsize)
+            .map(|y| (0..self.hsize).map(|x| self.sample(world, x, y)).collect())
+            .collect();
+
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        let background = color!(1, 1, 1);
+
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                let current = match &samples[y][x] {
+                    Some(sample) => sample,
+                    None => {
+                        image.write_pixel(x, y, &background);
+                        continue;
+                    }
+                };
+
+                let neighbors = [
+                    x.checked_sub(1).map(|x| (x, y)),
+                    y.checked_sub(1).map(|y| (x, y)),
+                    (x + 1 < self.hsize).then_some((x + 1, y)),
+                    (y + 1 < self.vsize).then_some((x, y + 1)),
+                ];
+
+                let edge = neighbors
+                    .iter()
+                    .flatten()
+                    .any(|&(nx, ny)| match &samples[ny][nx] {
+                        Some(neighbor) => current.is_discontinuous(neighbor),
+                        None => true,
+                    });
+
+                image.write_pixel(x, y, if edge { line_color } else { &background });
+            }
+        }
+
+        image
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        cubes::Cube,
+        lights::{Light, PointLight},
+        planes::Plane,
+        spheres::Sphere,
+        transformations::*,
+    };
+
+    #[test]
+    fn test_constructor() {
+        let camera = Camera::new(160, 120, PI / 2.0);
+
+        assert_eq!(160, camera.hsize());
+        assert_eq!(120, camera.vsize());
+        assert_eq!(PI / 2.0, camera.field_of_view());
+        assert_eq!(Matrix::identity(), camera.transform);
+    }
+
+    #[test]
+    fn test_pixel_size() {
+        let camera = Camera::new(200, 125, PI / 2.0);
+        assert!((0.01 - camera.pixel_size).abs() < EPSILON);
+
+        let camera = Camera::new(125, 200, PI / 2.0);
+        assert!((0.01 - camera.pixel_size).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_ray_for_pixel() {
+        let camera = Camera::new(201, 101, PI / 2.0);
+
+        let ray = camera.ray_for_pixel(100, 50);
+        assert_eq!(point!(0, 0, 0), ray.origin);
+        assert_eq!(vector!(0, 0, -1), ray.direction);
+
+        let ray = camera.ray_for_pixel(0, 0);
+        assert_eq!(point!(0, 0, 0), ray.origin);
+        assert_eq!(vector!(0.66519, 0.33259, -0.66851), ray.direction);
+
+        let mut camera = Camera::new(201, 101, PI / 2.0);
+        camera.set_transform(rotation(Axis::Y, PI / 4.0) * translation(0.0, -2.0, 5.0));
+        let ray = camera.ray_for_pixel(100, 50);
+        assert_eq!(point!(0, 2, -5), ray.origin);
+        assert_eq!(
+            vector!(2f64.sqrt() / 2.0, 0, -2f64.sqrt() / 2.0),
+            ray.direction
+        );
+    }
+
+    #[test]
+    fn test_render_with_progress() {
+        let world = World::default();
+        let mut camera = Camera::new(7, 5, PI / 2.0);
+        camera.set_transform(view_transform(
+            point!(0, 0, -5),
+            point!(0, 0, 0),
+            vector!(0, 1, 0),
+        ));
+
+        let mut calls = Vec::new();
+        let image = camera.render_with_progress(&world, |done, total| calls.push((done, total)));
+
+        assert_eq!((1..=5).map(|done| (done, 5)).collect::<Vec<_>>(), calls);
+        assert_eq!(camera.render(&world), image);
+    }
+
+    #[test]
+    fn test_render_to_ppm() {
+        let world = World::default();
+        let mut camera = Camera::new(21, 11, PI / 2.0);
+        camera.set_transform(view_transform(
+            point!(0, 0, -5),
+            point!(0, 0, 0),
+            vector!(0, 1, 0),
+        ));
+
+        let mut buffered = Vec::new();
+        camera.render(&world).write_out(&mut buffered).unwrap();
+
+        let mut streamed = Vec::new();
+        camera.render_to_ppm(&world, &mut streamed).unwrap();
+
+        assert_eq!(buffered, streamed);
+    }
+
+    #[test]
+    fn test_ray_for_pixel_offset() {
+        let camera = Camera::new(201, 101, PI / 2.0);
+
+        let ray = camera.ray_for_pixel_offset(100, 50, 0.5, 0.5);
+        assert_eq!(camera.ray_for_pixel(100, 50).direction, ray.direction);
+
+        let ray = camera.ray_for_pixel_offset(0, 0, 1.5, 0.5);
+        assert_eq!(camera.ray_for_pixel(1, 0).direction, ray.direction);
+    }
+
+    #[test]
+    fn test_render() {
+        let world = World::default();
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        camera.set_transform(view_transform(
+            point!(0, 0, -5),
+            point!(0, 0, 0),
+            vector!(0, 1, 0),
+        ));
+
+        let image = camera.render(&world);
+        assert_eq!(color!(0.38066, 0.47583, 0.2855), image.pixel_at(5, 5));
+    }
+
+    #[test]
+    fn test_render_parallel() {
+        let world = World::default();
+        let mut camera = Camera::new(13, 7, PI / 2.0);
+        camera.set_transform(view_transform(
+            point!(0, 0, -5),
+            point!(0, 0, 0),
+            vector!(0, 1, 0),
+        ));
+
+        let serial = camera.render(&world);
+        let parallel = camera.render_parallel(&world);
+
+        for y in 0..7 {
+            for x in 0..13 {
+                let (a, b) = (serial.pixel_at(x, y), parallel.pixel_at(x, y));
+                assert_eq!((a.0, a.1, a.2), (b.0, b.1, b.2));
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_wireframe() {
+        let mut world = World::new();
+        world.objects.push(Box::new(Sphere::new()));
+        let mut camera = Camera::new(21, 21, PI / 3.0);
+        camera.set_transform(view_transform(
+            point!(0, 0, -5),
+            point!(0, 0, 0),
+            vector!(0, 1, 0),
+        ));
+
+        let line = color!(1, 0, 0);
+        let background = color!(1, 1, 1);
+        let image = camera.render_wireframe(&world, &line);
+
+        assert_eq!(background, image.pixel_at(0, 0));
+        assert_eq!(background, image.pixel_at(10, 10));
+
+        for i in 8..13 {
+            assert_eq!(background, image.pixel_at(i, 10));
+            assert_eq!(background, image.pixel_at(10, i));
+        }
+        for (x, y) in [(7, 10), (13, 10), (10, 7), (10, 13)] {
+            assert_eq!(line, image.pixel_at(x, y));
+        }
+        for (x, y) in [(6, 10), (14, 10), (10, 6), (10, 14)] {
+            assert_eq!(background, image.pixel_at(x, y));
+        }
+    }
+
+    #[test]
+    fn test_pixel_for_point() {
+        let mut camera = Camera::new(201, 101, PI / 2.0);
+        camera.set_transform(rotation(Axis::Y, PI / 4.0) * translation(0.0, -2.0, 5.0));
+
+        for (px, py) in [(0, 0), (100, 50), (200, 100), (37, 81)] {
+            let ray = camera.ray_for_pixel(px, py);
+            let (x, y) = camera.pixel_for_point(&ray.position(3.0)).unwrap();
+            assert!((x - px as f64).abs() < EPSILON);
+            assert!((y - py as f64).abs() < EPSILON);
+        }
+
+        let ray = camera.ray_for_pixel(100, 50);
+        assert_eq!(None, camera.pixel_for_point(&ray.position(-3.0)));
+    }
+
+    #[test]
+    fn test_render_motion_vectors() {
+        let mut world = World::new();
+        world.objects.push(Box::new(Sphere::new()));
+
+        let mut wall = Plane::new();
+        wall.set_transform(translation(0.0, 0.0, 10.0) * rotation(Axis::X, PI / 2.0));
+        world.objects.push(Box::new(wall));
+
+        let mut camera = Camera::new(21, 21, PI / 3.0);
+        camera.set_transform(view_transform(
+            point!(0, 0, -5),
+            point!(0, 0, 0),
+            vector!(0, 1, 0),
+        ));
+
+        let still = camera.render_motion_vectors(&world, &camera, &[]);
+        for (x, y) in [(10, 10), (0, 0), (20, 10)] {
+            assert!(still.pixel_at(x, y).0.abs() < EPSILON);
+            assert!(still.pixel_at(x, y).1.abs() < EPSILON);
+        }
+
+        // panning right: the near sphere slides further across the screen than the far wall
+        let mut prev_camera = Camera::new(21, 21, PI / 3.0);
+        prev_camera.set_transform(view_transform(
+            point!(-0.5, 0, -5),
+            point!(-0.5, 0, 0),
+            vector!(0, 1, 0),
+        ));
+
+        let image = camera.render_motion_vectors(&world, &prev_camera, &[]);
+
+        let sphere = image.pixel_at(10, 10);
+        let wall = image.pixel_at(20, 10);
+        assert!(sphere.0 < -1.0);
+        assert!(sphere.1.abs() < EPSILON);
+        assert!(wall.0 < 0.0);
+        assert!(wall.0 > sphere.0);
+        assert!(wall.1.abs() < EPSILON);
+
+        // a still camera with the sphere having slid sideways since the last frame; the wall has
+        // no previous transform, so it stayed put
+        let prev_transforms = [translation(-0.5, 0.0, 0.0)];
+        let image = camera.render_motion_vectors(&world, &camera, &prev_transforms);
+
+        let sphere = image.pixel_at(10, 10);
+        assert!(sphere.0.abs() > 0.5);
+        assert!(sphere.1.abs() < EPSILON);
+        for (x, y) in [(0, 0), (20, 10)] {
+            assert!(image.pixel_at(x, y).0.abs() < EPSILON);
+            assert!(image.pixel_at(x, y).1.abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_squeeze() {
+        let mut world = World::new();
+        world.objects.push(Box::new(Sphere::new()));
+        world.lights.push(Light::from(PointLight::new(
+            point!(0, 0, -10),
+            color!(1, 1, 1),
+        )));
+
+        let mut camera = Camera::new(41, 41, PI / 3.0);
+        camera.set_transform(view_transform(
+            point!(0, 0, -5),
+            point!(0, 0, 0),
+            vector!(0, 1, 0),
+        ));
+        assert_eq!(1.0, camera.squeeze());
+        camera.set_squeeze(2.0);
+
+        let extent = |image: &Canvas| {
+            let covered = |x: usize, y: usize| image.pixel_at(x, y) != color!(0, 0, 0);
+            let width = (0..image.width)
+                .filter(|&x| covered(x, image.height / 2))
+                .count();
+            let height = (0..image.height)
+                .filter(|&y| covered(image.width / 2, y))
+                .count();
+            (width as isize, height as isize)
+        };
+
+        let image = camera.render(&world);
+        let (width, height) = extent(&image);
+        assert!(width < height);
+        assert!((2 * width - height).abs() <= 2);
+
+        let image = image.desqueeze(camera.squeeze());
+        assert_eq!(82, image.width);
+        let (width, height) = extent(&image);
+        assert!((width - height).abs() <= 2);
+
+        for (width, height) in [(0, 10), (10, 0)] {
+            let empty = Canvas::new(width, height).desqueeze(camera.squeeze());
+            assert_eq!((width, height), (empty.width, empty.height));
+        }
+
+        let ray = camera.ray_for_pixel(5, 30);
+        let (x, y) = camera.pixel_for_point(&ray.position(2.0)).unwrap();
+        assert!((x - 5.0).abs() < EPSILON);
+        assert!((y - 30.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_render_antialiased() {
+        // from inside a flat, fully ambient sphere every sample is the same color
+        let mut sphere = Sphere::new();
+        sphere.set_transform(scaling(10.0, 10.0, 10.0));
+        sphere.material.ambient = 1.0;
+        sphere.material.diffuse = 0.0;
+        sphere.material.specular = 0.0;
+        let mut world = World::new();
+        world.objects.push(Box::new(sphere));
+        world.lights.push(Light::from(PointLight::new(
+            point!(0, 0, 0),
+            color!(1, 1, 1),
+        )));
+
+        let camera = Camera::new(5, 5, PI / 2.0);
+        assert_eq!(camera.render(&world), camera.render_antialiased(&world, 4));
+
+        let world = World::default();
+        let mut camera = Camera::new(3, 3, PI / 8.0);
+        camera.set_transform(view_transform(
+            point!(0, 0, -5),
+            point!(0, 0, 0),
+            vector!(0, 1, 0),
+        ));
+        let image = camera.render_antialiased(&world, 2);
+
+        for y in 0..3 {
+            for x in 0..3 {
+                let mut expected = color!(0, 0, 0);
+                for (i, j) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let (u, v) = (x * 2 + i, y * 2 + j);
+                    let dx = (i as f64 + jitter_offset(u, v, 0)) / 2.0;
+                    let dy = (j as f64 + jitter_offset(u, v, 1)) / 2.0;
+                    expected =
+                        expected + world.color_at(&camera.ray_for_pixel_offset(x, y, dx, dy));
+                }
+
+                assert_eq!(expected / 4.0, image.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_depth_of_field() {
+        let world = World::default();
+        let transform = view_transform(point!(0, 0, -5), point!(0, 0, 0), vector!(0, 1, 0));
+
+        let mut pinhole = Camera::new(11, 11, PI / 2.0);
+        pinhole.set_transform(transform);
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        camera.set_transform(transform);
+        camera.set_focal_distance(5.0);
+        assert_eq!(pinhole.render(&world), camera.render(&world));
+
+        camera.set_aperture(0.5);
+        let offsets = [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)];
+        let rays: Vec<_> = offsets
+            .iter()
+            .map(|&(dx, dy)| camera.ray_for_pixel_offset(5, 5, dx, dy))
+            .collect();
+
+        for (i, ray) in rays.iter().enumerate() {
+            assert!((ray.origin - point!(0, 0, -5)).magnitude() <= 0.25);
+            assert!(rays[..i].iter().all(|other| other.origin != ray.origin));
+        }
+    }
+
+    #[test]
+    fn test_depth_of_field_blur() {
+        // a flat, fully ambient card so only coverage changes a pixel's color
+        let card = |z: f64| {
+            let mut cube = Cube::new();
+            cube.set_transform(translation(0.0, 0.0, z) * scaling(1.0, 1.0, 0.01));
+            cube.material.ambient = 1.0;
+            cube.material.diffuse = 0.0;
+            cube.material.specular = 0.0;
+
+            let mut world = World::new();
+            world.objects.push(Box::new(cube));
+            world.lights.push(Light::from(PointLight::new(
+                point!(0, 0, -10),
+                color!(1, 1, 1),
+            )));
+            world
+        };
+        let transform = view_transform(point!(0, 0, -5), point!(0, 0, 0), vector!(0, 1, 0));
+
+        let mut pinhole = Camera::new(21, 21, PI / 3.0);
+        pinhole.set_transform(transform);
+        let mut camera = Camera::new(21, 21, PI / 3.0);
+        camera.set_transform(transform);
+        camera.set_focal_distance(5.0);
+        camera.set_aperture(1.0);
+
+        // on the focal plane every lens sample agrees with the pinhole
+        let in_focus = card(0.0);
+        assert_eq!(pinhole.render(&in_focus), camera.render(&in_focus));
+
+        // behind it the card's edges pick up partial coverage
+        let out_of_focus = card(3.0);
+        let sharp = pinhole.render(&out_of_focus);
+        let blurred = camera.render(&out_of_focus);
+        assert_ne!(sharp, blurred);
+        assert!((0..21).any(|x| {
+            let red = blurred.pixel_at(x, 10).0;
+            red > 0.0 && red < 1.0
+        }));
+        assert!((0..21).all(|x| [0.0, 1.0].contains(&sharp.pixel_at(x, 10).0)));
+        assert_eq!(blurred, camera.render_parallel(&out_of_focus));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut camera = Camera::new(160, 120, PI / 3.0);
+        camera.set_transform(view_transform(
+            point!(1, 2, -5),
+            point!(0, 0, 0),
+            vector!(0, 1, 0),
+        ));
+        camera.set_aperture(0.1);
+        camera.set_focal_distance(4.0);
+
+        let json = serde_json::to_string(&camera).unwrap();
+        let loaded: Camera = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(camera.hsize(), loaded.hsize());
+        assert_eq!(camera.vsize(), loaded.vsize());
+        assert_eq!(camera.transform, loaded.transform);
+        assert_eq!(camera.pixel_size, loaded.pixel_size);
+        assert_eq!(camera.aperture(), loaded.aperture());
+
+        let (ray, loaded_ray) = (camera.ray_for_pixel(10, 20), loaded.ray_for_pixel(10, 20));
+        assert_eq!(ray.origin, loaded_ray.origin);
+        assert_eq!(ray.direction, loaded_ray.direction);
+
+        let mut singular: serde_json::Value = serde_json::from_str(&json).unwrap();
+        singular["transform"] = serde_json::json!([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0]
+        ]);
+        assert!(serde_json::from_value::<Camera>(singular).is_err());
+    }
+}
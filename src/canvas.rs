@@ -1,21 +1,26 @@
 use crate::{convert::u8_to_str, tuples::*};
-use std::io::{self, Write};
+use std::{
+  error::Error,
+  f64::consts::PI,
+  fmt,
+  io::{self, Read, Write},
+};
 
-type Pixel = (f64, f64, f64);
+pub(crate) type Pixel = (f64, f64, f64);
 
-struct PPM<'a, T: Write> {
+pub(crate) struct Ppm<'a, T: Write> {
   buffer: &'a mut T,
   current_line_len: usize,
 }
 
-impl<'a, T: Write> PPM<'a, T> {
-  fn new(width: usize, height: usize, buffer: &'a mut T) -> Result<Self, io::Error> {
-    buffer.write(b"P3\n")?;
-    buffer.write(width.to_string().as_bytes())?;
-    buffer.write(b" ")?;
-    buffer.write(height.to_string().as_bytes())?;
-    buffer.write(b"\n")?;
-    buffer.write(b"255\n")?;
+impl<'a, T: Write> Ppm<'a, T> {
+  pub(crate) fn new(width: usize, height: usize, buffer: &'a mut T) -> Result<Self, io::Error> {
+    buffer.write_all(b"P3\n")?;
+    buffer.write_all(width.to_string().as_bytes())?;
+    buffer.write_all(b" ")?;
+    buffer.write_all(height.to_string().as_bytes())?;
+    buffer.write_all(b"\n")?;
+    buffer.write_all(b"255\n")?;
 
     Ok(Self {
       buffer,
@@ -24,22 +29,35 @@ impl<'a, T: Write> PPM<'a, T> {
   }
 
   fn new_line(&mut self) -> Result<(), io::Error> {
-    self.buffer.write(b"\n")?;
+    self.buffer.write_all(b"\n")?;
     self.current_line_len = 0;
     Ok(())
   }
+
+  pub(crate) fn write_row<I>(&mut self, row: I, tone_map: ToneMap) -> Result<(), io::Error>
+  where
+    I: IntoIterator<Item = Pixel>,
+  {
+    for (r, g, b) in row {
+      self.write_all(clamp_value(&tone_map.apply(r)).as_bytes())?;
+      self.write_all(clamp_value(&tone_map.apply(g)).as_bytes())?;
+      self.write_all(clamp_value(&tone_map.apply(b)).as_bytes())?;
+    }
+
+    self.new_line()
+  }
 }
 
-impl<T: Write> Write for PPM<'_, T> {
+impl<T: Write> Write for Ppm<'_, T> {
   fn write(&mut self, value: &[u8]) -> Result<usize, io::Error> {
     if self.current_line_len + value.len() + 2 > 70 {
       self.new_line()?;
     } else if self.current_line_len != 0 {
-      self.buffer.write(b" ")?;
+      self.buffer.write_all(b" ")?;
       self.current_line_len += 1;
     }
 
-    self.buffer.write(value)?;
+    self.buffer.write_all(value)?;
     self.current_line_len += value.len();
     Ok(value.len())
   }
@@ -50,22 +68,87 @@ impl<T: Write> Write for PPM<'_, T> {
   }
 }
 
-fn clamp_value(value: &f64) -> &'static str {
-  if *value < 0.0 {
-    return "0";
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ToneMap {
+  None,
+  Reinhard,
+  AcesFilmic,
+  Hable,
+}
+
+impl ToneMap {
+  pub fn apply(&self, value: f64) -> f64 {
+    let value = value.max(0.0);
+
+    match self {
+      ToneMap::None => value,
+      ToneMap::Reinhard => value / (1.0 + value),
+      // Narkowicz's fit of the ACES reference rendering transform
+      ToneMap::AcesFilmic => {
+        (value * (2.51 * value + 0.03)) / (value * (2.43 * value + 0.59) + 0.14)
+      }
+      // John Hable's Uncharted 2 curve, normalized by the white point
+      ToneMap::Hable => {
+        const WHITE_POINT: f64 = 11.2;
+        const EXPOSURE_BIAS: f64 = 2.0;
+
+        let curve = |x: f64| {
+          let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+          ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+        };
+
+        curve(value * EXPOSURE_BIAS) / curve(WHITE_POINT)
+      }
+    }
   }
+}
+
+fn to_byte(value: f64) -> u8 {
+  (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn clamp_value(value: &f64) -> &'static str {
+  u8_to_str(to_byte(*value))
+}
+
+const EDGE_THRESHOLD: f64 = 0.05;
+
+#[derive(Debug)]
+pub enum ParseError {
+  Io(io::Error),
+  BadMagic(String),
+  BadNumber(String),
+  // a sample above the header's maximum value
+  OutOfRange(usize),
+  Truncated,
+}
 
-  if *value > 1.0 {
-    return "255";
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ParseError::Io(err) => write!(f, "failed to read ppm: {err}"),
+      ParseError::BadMagic(magic) => write!(f, "expected P3 magic number, found {magic:?}"),
+      ParseError::BadNumber(token) => write!(f, "invalid number {token:?}"),
+      ParseError::OutOfRange(value) => write!(f, "sample {value} exceeds the maximum value"),
+      ParseError::Truncated => write!(f, "ppm data ended early"),
+    }
   }
+}
 
-  u8_to_str((value * 255.0).round() as u8)
+impl Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+  fn from(err: io::Error) -> Self {
+    ParseError::Io(err)
+  }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Canvas {
   pub width: usize,
   pub height: usize,
-  pixels: Vec<Vec<Pixel>>,
+  pixels: Vec<Pixel>,
 }
 
 impl Canvas {
@@ -73,54 +156,376 @@ impl Canvas {
     Self {
       width,
       height,
-      pixels: vec![vec![(0., 0., 0.); width]; height],
+      pixels: vec![(0., 0., 0.); width * height],
+    }
+  }
+
+  pub fn from_ppm<R: Read>(reader: &mut R) -> Result<Canvas, ParseError> {
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+
+    let mut tokens = contents
+      .lines()
+      .map(|line| line.split('#').next().unwrap_or_default())
+      .flat_map(str::split_whitespace);
+
+    match tokens.next() {
+      Some("P3") => {}
+      Some(magic) => return Err(ParseError::BadMagic(magic.to_string())),
+      None => return Err(ParseError::Truncated),
+    }
+
+    let parse = |token: &str| -> Result<usize, ParseError> {
+      token
+        .parse()
+        .map_err(|_| ParseError::BadNumber(token.to_string()))
+    };
+    let mut next_number = || parse(tokens.next().ok_or(ParseError::Truncated)?);
+
+    let width = next_number()?;
+    let height = next_number()?;
+    let maxval = next_number()?.max(1);
+    let scale = maxval as f64;
+
+    // the header can't promise more samples than the data holds, which also
+    // keeps a bogus size from overflowing or allocating before anything is read
+    let samples: Vec<&str> = tokens.collect();
+    let expected = width
+      .checked_mul(height)
+      .and_then(|pixels| pixels.checked_mul(3))
+      .ok_or(ParseError::Truncated)?;
+    if samples.len() < expected {
+      return Err(ParseError::Truncated);
+    }
+
+    let mut values = samples.iter().map(|token| match parse(token)? {
+      value if value > maxval => Err(ParseError::OutOfRange(value)),
+      value => Ok(value as f64 / scale),
+    });
+    let mut next_value = || values.next().unwrap_or(Err(ParseError::Truncated));
+
+    let mut canvas = Canvas::new(width, height);
+    for pixel in canvas.pixels.iter_mut() {
+      *pixel = (next_value()?, next_value()?, next_value()?);
     }
+
+    Ok(canvas)
   }
 
   pub fn write_pixel(&mut self, x: usize, y: usize, tuple: &Tuple) {
-    if let Some(row) = self.pixels.get_mut(y) {
-      if let Some(pixel) = row.get_mut(x) {
-        *pixel = tuple.as_color();
+    if x < self.width && y < self.height {
+      self.pixels[y * self.width + x] = tuple.as_color();
+    }
+  }
+
+  // out of bounds reads as black, mirroring write_pixel ignoring them
+  pub fn pixel_at(&self, x: usize, y: usize) -> Tuple {
+    if x >= self.width || y >= self.height {
+      return color!(0, 0, 0);
+    }
+
+    let (r, g, b) = self.pixel(x, y);
+    color!(r, g, b)
+  }
+
+  fn pixel(&self, x: usize, y: usize) -> Pixel {
+    self.pixels[y * self.width + x]
+  }
+
+  fn rows(&self) -> impl Iterator<Item = &[Pixel]> {
+    self.pixels.chunks(self.width.max(1))
+  }
+
+  fn rows_mut(&mut self) -> impl Iterator<Item = &mut [Pixel]> {
+    self.pixels.chunks_mut(self.width.max(1))
+  }
+
+  fn convolve(&self, kernel: &[f64], horizontal: bool) -> Canvas {
+    let radius = (kernel.len() / 2) as isize;
+    let mut output = Canvas::new(self.width, self.height);
+
+    for (y, row) in output.rows_mut().enumerate() {
+      for (x, pixel) in row.iter_mut().enumerate() {
+        *pixel = kernel
+          .iter()
+          .enumerate()
+          .fold((0.0, 0.0, 0.0), |(r, g, b), (i, weight)| {
+            let offset = i as isize - radius;
+            let (sx, sy) = if horizontal {
+              (
+                (x as isize + offset).clamp(0, self.width as isize - 1) as usize,
+                y,
+              )
+            } else {
+              (
+                x,
+                (y as isize + offset).clamp(0, self.height as isize - 1) as usize,
+              )
+            };
+            let (r2, g2, b2) = self.pixel(sx, sy);
+
+            (r + r2 * weight, g + g2 * weight, b + b2 * weight)
+          });
+      }
+    }
+
+    output
+  }
+
+  pub fn blur(&self, sigma: f64) -> Canvas {
+    if sigma <= 0.0 {
+      return self.clone();
+    }
+
+    let radius = (sigma * 3.0).ceil() as isize;
+    let kernel: Vec<f64> = (-radius..=radius)
+      .map(|i| (-((i * i) as f64) / (2.0 * sigma * sigma)).exp())
+      .collect();
+    let total: f64 = kernel.iter().sum();
+    let kernel: Vec<f64> = kernel.iter().map(|weight| weight / total).collect();
+
+    self.convolve(&kernel, true).convolve(&kernel, false)
+  }
+
+  pub fn edges(&self, low_sigma: f64, high_sigma: f64) -> Canvas {
+    let low = self.blur(low_sigma);
+    let high = self.blur(high_sigma);
+    let mut output = Canvas::new(self.width, self.height);
+
+    for (y, row) in output.rows_mut().enumerate() {
+      for (x, pixel) in row.iter_mut().enumerate() {
+        let (r1, g1, b1) = low.pixel(x, y);
+        let (r2, g2, b2) = high.pixel(x, y);
+        let response = ((r1 - r2).abs() + (g1 - g2).abs() + (b1 - b2).abs()) / 3.0;
+
+        if response > EDGE_THRESHOLD {
+          *pixel = (1.0, 1.0, 1.0);
+        }
       }
     }
+
+    output
   }
 
-  fn write_to_writer<T: Write>(&self, buffer: &mut T) -> Result<(), io::Error> {
-    let mut output = PPM::new(self.width, self.height, buffer)?;
+  pub fn bloom(&self, threshold: f64, spread: usize, intensity: f64) -> Canvas {
+    let mut bright = Canvas::new(self.width, self.height);
 
-    for row in &self.pixels {
-      for (r, g, b) in row {
-        output.write(clamp_value(r).as_bytes())?;
-        output.write(clamp_value(g).as_bytes())?;
-        output.write(clamp_value(b).as_bytes())?;
+    for (y, row) in bright.rows_mut().enumerate() {
+      for (x, pixel) in row.iter_mut().enumerate() {
+        let (r, g, b) = self.pixel(x, y);
+
+        if (r + g + b) / 3.0 > threshold {
+          *pixel = (r, g, b);
+        }
       }
+    }
+
+    let glow = bright.blur(spread as f64 / 3.0);
+    let mut output = self.clone();
 
-      output.new_line()?;
+    for (y, row) in output.rows_mut().enumerate() {
+      for (x, (r, g, b)) in row.iter_mut().enumerate() {
+        let (r2, g2, b2) = glow.pixel(x, y);
+
+        *r += r2 * intensity;
+        *g += g2 * intensity;
+        *b += b2 * intensity;
+      }
     }
 
-    Ok(())
+    output
   }
 
-  pub fn to_string(&self) -> String {
-    let mut buffer = Vec::with_capacity((self.width + 1) * self.height * 3);
-    self.write_to_writer(&mut buffer).unwrap();
-    String::from_utf8(buffer).unwrap()
+  pub fn make_tileable(&self, blend: usize) -> Canvas {
+    let mut output = self.clone();
+    let mix = |a: Pixel, b: Pixel, weight: f64| {
+      (
+        a.0 * (1.0 - weight) + b.0 * weight,
+        a.1 * (1.0 - weight) + b.1 * weight,
+        a.2 * (1.0 - weight) + b.2 * weight,
+      )
+    };
+
+    // fade each border towards the opposite one, meeting halfway at the edge
+    let horizontal = blend.min(self.width / 2);
+    for row in output.rows_mut() {
+      for x in 0..horizontal {
+        let weight = 0.5 * (1.0 - x as f64 / horizontal as f64);
+        let (near, far) = (row[x], row[self.width - 1 - x]);
+
+        row[x] = mix(near, far, weight);
+        row[self.width - 1 - x] = mix(far, near, weight);
+      }
+    }
+
+    let vertical = blend.min(self.height / 2);
+    for y in 0..vertical {
+      let weight = 0.5 * (1.0 - y as f64 / vertical as f64);
+
+      for x in 0..self.width {
+        let (near, far) = (output.pixel(x, y), output.pixel(x, self.height - 1 - y));
+
+        output.pixels[y * self.width + x] = mix(near, far, weight);
+        output.pixels[(self.height - 1 - y) * self.width + x] = mix(far, near, weight);
+      }
+    }
+
+    output
+  }
+
+  pub fn posterize(&self, levels: usize) -> Canvas {
+    let steps = levels.max(2) as f64 - 1.0;
+    let snap = |value: f64| (value.clamp(0.0, 1.0) * steps).round() / steps;
+
+    let mut output = self.clone();
+
+    for (r, g, b) in output.pixels.iter_mut() {
+      *r = snap(*r);
+      *g = snap(*g);
+      *b = snap(*b);
+    }
+
+    output
+  }
+
+  pub fn quantize(&self, palette: &[Tuple]) -> Canvas {
+    let mut output = self.clone();
+
+    for pixel in output.pixels.iter_mut() {
+      let (r, g, b) = *pixel;
+      let nearest = palette
+        .iter()
+        .map(|color| color.as_color())
+        .min_by(|first, second| {
+          let distance =
+            |(r2, g2, b2): &Pixel| (r - r2).powi(2) + (g - g2).powi(2) + (b - b2).powi(2);
+          distance(first).total_cmp(&distance(second))
+        });
+
+      if let Some(color) = nearest {
+        *pixel = color;
+      }
+    }
+
+    output
+  }
+
+  // stretches the image horizontally to undo an anamorphic squeeze
+  pub fn desqueeze(&self, factor: f64) -> Canvas {
+    // nothing to stretch
+    if self.width == 0 || self.height == 0 {
+      return self.clone();
+    }
+
+    let width = ((self.width as f64 * factor).round() as usize).max(1);
+    let mut output = Canvas::new(width, self.height);
+    let last = self.width.saturating_sub(1) as f64;
+
+    for (y, row) in output.rows_mut().enumerate() {
+      for (x, pixel) in row.iter_mut().enumerate() {
+        let source = ((x as f64 + 0.5) / factor - 0.5).clamp(0.0, last);
+        let (left, right) = (source.floor() as usize, source.ceil() as usize);
+        let weight = source - source.floor();
+        let (a, b) = (self.pixel(left, y), self.pixel(right, y));
+
+        *pixel = (
+          a.0 + (b.0 - a.0) * weight,
+          a.1 + (b.1 - a.1) * weight,
+          a.2 + (b.2 - a.2) * weight,
+        );
+      }
+    }
+
+    output
+  }
+
+  // treats the canvas as an equirectangular environment map
+  pub fn sample_equirectangular(&self, direction: &Tuple) -> Tuple {
+    // an empty map has nothing to give
+    if self.width == 0 || self.height == 0 {
+      return color!(0, 0, 0);
+    }
+
+    let direction = direction.normalize();
+    let u = 0.5 + direction.0.atan2(direction.2) / (2.0 * PI);
+    let v = 0.5 - direction.1.clamp(-1.0, 1.0).asin() / PI;
+
+    let x = ((u * self.width as f64) as usize).min(self.width - 1);
+    let y = ((v * self.height as f64) as usize).min(self.height - 1);
+    let (r, g, b) = self.pixel(x, y);
+
+    color!(r, g, b)
+  }
+
+  fn write_to_writer<T: Write>(&self, buffer: &mut T, tone_map: ToneMap) -> Result<(), io::Error> {
+    let mut output = Ppm::new(self.width, self.height, buffer)?;
+
+    for row in self.rows() {
+      output.write_row(row.iter().copied(), tone_map)?;
+    }
+
+    Ok(())
   }
 
   pub fn write_out<T: Write>(&self, writer: &mut T) -> Result<(), io::Error> {
-    self.write_to_writer(writer)?;
+    self.write_to_writer(writer, ToneMap::None)?;
+    Ok(())
+  }
+
+  #[cfg(feature = "png")]
+  pub fn write_png<W: Write>(&self, writer: &mut W) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data: Vec<u8> = self
+      .pixels
+      .iter()
+      .flat_map(|&(r, g, b)| [to_byte(r), to_byte(g), to_byte(b)])
+      .collect();
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
+    writer.finish()
+  }
+
+  pub fn write_out_tonemapped<T: Write>(
+    &self,
+    writer: &mut T,
+    tone_map: ToneMap,
+  ) -> Result<(), io::Error> {
+    self.write_to_writer(writer, tone_map)?;
     Ok(())
   }
+}
 
-  #[cfg(test)]
-  fn iter_mut(&mut self) -> impl Iterator<Item = &mut Vec<Pixel>> {
-    self.pixels.iter_mut()
+impl fmt::Display for Canvas {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut buffer = Vec::with_capacity((self.width + 1) * self.height * 3);
+    self
+      .write_to_writer(&mut buffer, ToneMap::None)
+      .map_err(|_| fmt::Error)?;
+    f.write_str(std::str::from_utf8(&buffer).map_err(|_| fmt::Error)?)
   }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
+  use crate::constants::EPSILON;
+
+  #[test]
+  fn test_pixel_at() {
+    let mut canvas = Canvas::new(4, 3);
+    let red = color!(1, 0, 0);
+    canvas.write_pixel(2, 1, &red);
+    canvas.write_pixel(4, 0, &red);
+
+    assert_eq!(red, canvas.pixel_at(2, 1));
+    assert_eq!(color!(0, 0, 0), canvas.pixel_at(1, 2));
+    assert_eq!(color!(0, 0, 0), canvas.pixel_at(4, 0));
+    assert_eq!(color!(0, 0, 0), canvas.pixel_at(0, 3));
+  }
 
   #[test]
   fn test_ppm_header() {
@@ -156,10 +561,10 @@ mod test {
   fn test_ppm_line_length() {
     let mut canvas = Canvas::new(10, 2);
 
-    let color = color!(1, 0.8, 0.6).as_color();
-    for row in canvas.iter_mut() {
-      for pixel in row.iter_mut() {
-        *pixel = color;
+    let color = color!(1, 0.8, 0.6);
+    for y in 0..2 {
+      for x in 0..10 {
+        canvas.write_pixel(x, y, &color);
       }
     }
 
@@ -191,4 +596,298 @@ mod test {
 
     assert!(string.ends_with('\n'));
   }
+
+  #[cfg(feature = "png")]
+  #[test]
+  fn test_write_png() {
+    let mut canvas = Canvas::new(3, 2);
+    canvas.write_pixel(0, 0, &color!(1.5, 0, 0));
+    canvas.write_pixel(1, 0, &color!(0, 0.5, 0));
+    canvas.write_pixel(2, 1, &color!(-0.5, 0, 1));
+
+    let mut buffer = Vec::new();
+    canvas.write_png(&mut buffer).unwrap();
+
+    let decoder = png::Decoder::new(io::Cursor::new(buffer));
+    let mut reader = decoder.read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut data).unwrap();
+
+    assert_eq!((3, 2), (info.width, info.height));
+    assert_eq!(png::ColorType::Rgb, info.color_type);
+
+    for y in 0..2 {
+      for x in 0..3 {
+        let pixel = canvas.pixel_at(x, y);
+        let offset = (y * 3 + x) * 3;
+        assert_eq!(
+          [to_byte(pixel.0), to_byte(pixel.1), to_byte(pixel.2)],
+          data[offset..offset + 3]
+        );
+      }
+    }
+    assert_eq!([255, 0, 0], data[0..3]);
+    assert_eq!([0, 0, 255], data[15..18]);
+  }
+
+  #[test]
+  fn test_from_ppm() {
+    let mut canvas = Canvas::new(5, 3);
+    canvas.write_pixel(0, 0, &color!(1, 0, 0));
+    canvas.write_pixel(2, 1, &color!(0, 0.2, 0.4));
+    canvas.write_pixel(4, 2, &color!(0.6, 0.8, 1));
+
+    let parsed = Canvas::from_ppm(&mut canvas.to_string().as_bytes()).unwrap();
+    assert_eq!((5, 3), (parsed.width, parsed.height));
+    for y in 0..3 {
+      for x in 0..5 {
+        assert_eq!(canvas.pixel_at(x, y), parsed.pixel_at(x, y));
+      }
+    }
+
+    let ppm = "P3\n# a comment\n4 3\n255\n\
+      255 127 0  0 127 255  127 255 0  255 255 255\n\
+      0 0 0  255 0 0  0 255 0  0 0 255\n\
+      255 255 0  0 255 255  255 0 255  127 127 127 # trailing comment\n";
+    let canvas = Canvas::from_ppm(&mut ppm.as_bytes()).unwrap();
+    assert_eq!((4, 3), (canvas.width, canvas.height));
+    assert_eq!(color!(1, 0.49804, 0), canvas.pixel_at(0, 0));
+    assert_eq!(color!(1, 1, 1), canvas.pixel_at(3, 0));
+    assert_eq!(color!(0, 0, 1), canvas.pixel_at(3, 1));
+    assert_eq!(color!(0.49804, 0.49804, 0.49804), canvas.pixel_at(3, 2));
+
+    // readers shouldn't enforce the 70 character limit the writer uses
+    let ppm = format!("P3\n8 1\n255\n{}\n", "255 204 153 ".repeat(8));
+    assert!(ppm.lines().any(|line| line.len() > 70));
+    let canvas = Canvas::from_ppm(&mut ppm.as_bytes()).unwrap();
+    assert_eq!(color!(1, 0.8, 0.6), canvas.pixel_at(7, 0));
+
+    // samples can wrap anywhere, and maxval sets the scale
+    let ppm = "P3\n1 2\n100\n100\n75\n50 25 0\n0\n";
+    let canvas = Canvas::from_ppm(&mut ppm.as_bytes()).unwrap();
+    assert_eq!(color!(1, 0.75, 0.5), canvas.pixel_at(0, 0));
+    assert_eq!(color!(0.25, 0, 0), canvas.pixel_at(0, 1));
+  }
+
+  #[test]
+  fn test_from_ppm_errors() {
+    let ppm = "P32\n1 1\n255\n0 0 0\n";
+    assert!(matches!(
+      Canvas::from_ppm(&mut ppm.as_bytes()),
+      Err(ParseError::BadMagic(_))
+    ));
+
+    let ppm = "P3\n2 1\n255\n0 0 0 255\n";
+    assert!(matches!(
+      Canvas::from_ppm(&mut ppm.as_bytes()),
+      Err(ParseError::Truncated)
+    ));
+
+    let ppm = "P3\n1 1\n255\n0 x 0\n";
+    assert!(matches!(
+      Canvas::from_ppm(&mut ppm.as_bytes()),
+      Err(ParseError::BadNumber(_))
+    ));
+
+    let ppm = "P3\n99999999999 99999999999\n255\n0 0 0\n";
+    assert!(matches!(
+      Canvas::from_ppm(&mut ppm.as_bytes()),
+      Err(ParseError::Truncated)
+    ));
+
+    let ppm = format!("P3\n{} 2\n255\n0 0 0\n", usize::MAX);
+    assert!(matches!(
+      Canvas::from_ppm(&mut ppm.as_bytes()),
+      Err(ParseError::Truncated)
+    ));
+
+    let ppm = "P3\n1 1\n100\n0 101 0\n";
+    assert!(matches!(
+      Canvas::from_ppm(&mut ppm.as_bytes()),
+      Err(ParseError::OutOfRange(101))
+    ));
+  }
+
+  #[test]
+  fn test_blur() {
+    let mut canvas = Canvas::new(11, 11);
+    canvas.write_pixel(5, 5, &color!(1, 1, 1));
+
+    let blurred = canvas.blur(1.0);
+    let (center, _, _) = blurred.pixel(5, 5);
+    let (neighbor, _, _) = blurred.pixel(4, 5);
+    let (corner, _, _) = blurred.pixel(0, 0);
+
+    assert!(center < 1.0);
+    assert!(neighbor > 0.0 && neighbor < center);
+    assert!(corner < neighbor);
+
+    let total: f64 = blurred.pixels.iter().map(|(r, _, _)| r).sum();
+    assert!((total - 1.0).abs() < EPSILON);
+  }
+
+  #[test]
+  fn test_edges() {
+    let mut canvas = Canvas::new(30, 30);
+    for y in 0..30 {
+      for x in 0..30 {
+        let color = if (8..22).contains(&x) && (8..22).contains(&y) {
+          color!(0, 0, 0)
+        } else {
+          color!(1, 1, 1)
+        };
+        canvas.write_pixel(x, y, &color);
+      }
+    }
+
+    let edges = canvas.edges(1.0, 2.0);
+
+    assert_eq!((1.0, 1.0, 1.0), edges.pixel(8, 15));
+    assert_eq!((1.0, 1.0, 1.0), edges.pixel(21, 15));
+    assert_eq!((1.0, 1.0, 1.0), edges.pixel(15, 8));
+    assert_eq!((1.0, 1.0, 1.0), edges.pixel(15, 21));
+
+    assert_eq!((0.0, 0.0, 0.0), edges.pixel(0, 0));
+    assert_eq!((0.0, 0.0, 0.0), edges.pixel(15, 15));
+    assert_eq!((0.0, 0.0, 0.0), edges.pixel(2, 29));
+  }
+
+  #[test]
+  fn test_posterize() {
+    let mut canvas = Canvas::new(4, 1);
+    canvas.write_pixel(0, 0, &color!(0.1, 0.49, 0.51));
+    canvas.write_pixel(1, 0, &color!(0.9, -0.5, 1.5));
+    canvas.write_pixel(2, 0, &color!(0.2, 0.4, 0.8));
+
+    let two = canvas.posterize(2);
+    assert_eq!((0.0, 0.0, 1.0), two.pixel(0, 0));
+    assert_eq!((1.0, 0.0, 1.0), two.pixel(1, 0));
+    assert_eq!((0.0, 0.0, 1.0), two.pixel(2, 0));
+    assert_eq!((0.0, 0.0, 0.0), two.pixel(3, 0));
+
+    let three = canvas.posterize(3);
+    assert_eq!((0.0, 0.5, 1.0), three.pixel(2, 0));
+  }
+
+  #[test]
+  fn test_quantize() {
+    let mut canvas = Canvas::new(2, 1);
+    canvas.write_pixel(0, 0, &color!(0.9, 0.1, 0.2));
+    canvas.write_pixel(1, 0, &color!(0.1, 0.2, 0.7));
+
+    let palette = [color!(1, 0, 0), color!(0, 1, 0), color!(0, 0, 1)];
+    let quantized = canvas.quantize(&palette);
+
+    assert_eq!((1.0, 0.0, 0.0), quantized.pixel(0, 0));
+    assert_eq!((0.0, 0.0, 1.0), quantized.pixel(1, 0));
+  }
+
+  #[test]
+  fn test_bloom() {
+    let mut canvas = Canvas::new(11, 11);
+    canvas.write_pixel(5, 5, &color!(3, 3, 3));
+    canvas.write_pixel(1, 1, &color!(0.5, 0.5, 0.5));
+
+    let bloomed = canvas.bloom(1.0, 3, 1.0);
+
+    let (r, _, _) = bloomed.pixel(4, 5);
+    assert!(r > 0.0);
+    let (r, _, _) = bloomed.pixel(5, 7);
+    assert!(r > 0.0);
+    let (r, _, _) = bloomed.pixel(5, 5);
+    assert!(r > 3.0);
+
+    assert_eq!((0.5, 0.5, 0.5), bloomed.pixel(1, 1));
+    assert_eq!((0.0, 0.0, 0.0), bloomed.pixel(0, 1));
+    assert_eq!((0.0, 0.0, 0.0), bloomed.pixel(1, 2));
+  }
+
+  #[test]
+  fn test_tone_map_curves() {
+    for tone_map in [ToneMap::Reinhard, ToneMap::AcesFilmic, ToneMap::Hable] {
+      assert!(tone_map.apply(0.0).abs() < EPSILON, "{tone_map:?}");
+      assert!(tone_map.apply(4.0) < 1.0, "{tone_map:?}");
+      assert!(tone_map.apply(4.0) > tone_map.apply(1.0), "{tone_map:?}");
+
+      let samples: Vec<f64> = (0..=100).map(|i| tone_map.apply(i as f64 / 10.0)).collect();
+      assert!(
+        samples.windows(2).all(|pair| pair[0] < pair[1]),
+        "{tone_map:?}"
+      );
+    }
+
+    assert_eq!(4.0, ToneMap::None.apply(4.0));
+    assert_eq!(0.0, ToneMap::None.apply(-1.0));
+  }
+
+  #[test]
+  fn test_write_out_tonemapped() {
+    let mut canvas = Canvas::new(2, 1);
+    canvas.write_pixel(0, 0, &color!(1, 0, 3));
+    canvas.write_pixel(1, 0, &color!(0.5, 1, 0));
+
+    let mut buffer = Vec::new();
+    canvas
+      .write_out_tonemapped(&mut buffer, ToneMap::Reinhard)
+      .unwrap();
+    let string = String::from_utf8(buffer).unwrap();
+    let mut lines = string.lines().skip(3);
+
+    assert_eq!("128 0 191 85 128 0", lines.next().unwrap());
+
+    let mut buffer = Vec::new();
+    canvas
+      .write_out_tonemapped(&mut buffer, ToneMap::None)
+      .unwrap();
+    assert_eq!(canvas.to_string(), String::from_utf8(buffer).unwrap());
+  }
+
+  #[test]
+  fn test_make_tileable() {
+    let mut canvas = Canvas::new(12, 10);
+    for y in 0..10 {
+      for x in 0..12 {
+        canvas.write_pixel(x, y, &color!(x as f64 / 11.0, y as f64 / 9.0, 0.5));
+      }
+    }
+
+    let tiled = canvas.make_tileable(3);
+
+    for y in 0..10 {
+      assert_eq!(tiled.pixel_at(0, y), tiled.pixel_at(11, y));
+    }
+    for x in 0..12 {
+      assert_eq!(tiled.pixel_at(x, 0), tiled.pixel_at(x, 9));
+    }
+
+    assert_eq!(canvas.pixel_at(5, 5), tiled.pixel_at(5, 5));
+    assert_eq!(canvas.pixel_at(3, 3), tiled.pixel_at(3, 3));
+    assert_ne!(canvas.pixel_at(1, 5), tiled.pixel_at(1, 5));
+
+    let untouched = canvas.make_tileable(0);
+    assert_eq!(canvas.pixel_at(0, 0), untouched.pixel_at(0, 0));
+  }
+  #[test]
+  fn test_sample_equirectangular() {
+    let mut canvas = Canvas::new(4, 2);
+    canvas.write_pixel(2, 0, &color!(1, 0, 0));
+    canvas.write_pixel(2, 1, &color!(0, 0, 1));
+
+    assert_eq!(
+      color!(1, 0, 0),
+      canvas.sample_equirectangular(&vector!(0, 1, 1))
+    );
+    assert_eq!(
+      color!(0, 0, 1),
+      canvas.sample_equirectangular(&vector!(0, -1, 1))
+    );
+
+    for (width, height) in [(0, 2), (4, 0)] {
+      let empty = Canvas::new(width, height);
+      assert_eq!(
+        color!(0, 0, 0),
+        empty.sample_equirectangular(&vector!(0, 1, 1))
+      );
+    }
+  }
 }
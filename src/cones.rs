@@ -0,0 +1,264 @@
+use crate::{
+    bounds::AABB,
+    constants::EPSILON,
+    intersections::Intersections,
+    materials::Material,
+    matrices::Matrix,
+    rays::Ray,
+    shapes::{Shape, Transform},
+    tuples::Tuple,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cone {
+    transform: Transform,
+    pub material: Material,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cone {
+    pub const fn new() -> Self {
+        Self {
+            transform: Transform::identity(),
+            material: Material::new(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+
+    // whether the ray at t is within the cone's radius at height y
+    fn check_cap(ray: &Ray, t: f64, y: f64) -> bool {
+        let x = ray.origin.0 + t * ray.direction.0;
+        let z = ray.origin.2 + t * ray.direction.2;
+
+        x.powi(2) + z.powi(2) <= y.powi(2)
+    }
+
+    fn intersect_caps(&self, ray: &Ray, ts: &mut Vec<f64>) {
+        if !self.closed || ray.direction.1.abs() < EPSILON {
+            return;
+        }
+
+        for y in [self.minimum, self.maximum] {
+            let t = (y - ray.origin.1) / ray.direction.1;
+            if Self::check_cap(ray, t, y) {
+                ts.push(t);
+            }
+        }
+    }
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for Cone {
+    fn transform(&self) -> &Matrix<4> {
+        self.transform.matrix()
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = Transform::new(transform);
+    }
+
+    fn inverse(&self) -> Option<Matrix<4>> {
+        self.transform.inverse()
+    }
+
+    fn inverse_transpose(&self) -> Option<Matrix<4>> {
+        self.transform.inverse_transpose()
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let mut ts = Vec::with_capacity(4);
+
+        let (o, d) = (ray.origin, ray.direction);
+        let a = d.0.powi(2) - d.1.powi(2) + d.2.powi(2);
+        let b = 2.0 * o.0 * d.0 - 2.0 * o.1 * d.1 + 2.0 * o.2 * d.2;
+        let c = o.0.powi(2) - o.1.powi(2) + o.2.powi(2);
+
+        let candidates = if a.abs() < EPSILON {
+            // the ray is parallel to one of the cone's halves
+            if b.abs() < EPSILON {
+                vec![]
+            } else {
+                vec![-c / (2.0 * b)]
+            }
+        } else {
+            let discriminant = b.powi(2) - 4.0 * a * c;
+
+            if discriminant < 0.0 {
+                return Intersections::empty();
+            }
+
+            let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+            let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+            vec![t0.min(t1), t0.max(t1)]
+        };
+
+        for t in candidates {
+            let y = o.1 + t * d.1;
+            if self.minimum < y && y < self.maximum {
+                ts.push(t);
+            }
+        }
+
+        self.intersect_caps(ray, &mut ts);
+        ts.sort_by(f64::total_cmp);
+
+        Intersections::from(&ts, self)
+    }
+
+    fn local_normal_at(&self, point: &Tuple) -> Tuple {
+        let dist = point.0.powi(2) + point.2.powi(2);
+
+        if dist < point.1.powi(2) && point.1 >= self.maximum - EPSILON {
+            vector!(0, 1, 0)
+        } else if dist < point.1.powi(2) && point.1 <= self.minimum + EPSILON {
+            vector!(0, -1, 0)
+        } else {
+            let mut y = dist.sqrt();
+            if point.1 > 0.0 {
+                y = -y;
+            }
+            vector!(point.0, y, point.2)
+        }
+    }
+
+    fn bounds(&self) -> AABB {
+        let limit = self.minimum.abs().max(self.maximum.abs());
+
+        AABB::new(
+            point!(-limit, self.minimum, -limit),
+            point!(limit, self.maximum, limit),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::SQRT_2;
+
+    #[test]
+    fn test_constructor() {
+        let cone = Cone::new();
+
+        assert_eq!(f64::NEG_INFINITY, cone.minimum);
+        assert_eq!(f64::INFINITY, cone.maximum);
+        assert!(!cone.closed);
+    }
+
+    #[test]
+    fn test_intersect() {
+        let cone = Cone::new();
+
+        let cases = [
+            (point!(0, 0, -5), vector!(0, 0, 1), 5.0, 5.0),
+            (point!(0, 0, -5), vector!(1, 1, 1), 8.66025, 8.66025),
+            (point!(1, 1, -5), vector!(-0.5, -1, 1), 4.55006, 49.44994),
+        ];
+
+        for (origin, direction, t0, t1) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(
+                Intersections::from(&[t0, t1], &cone),
+                cone.local_intersect(&ray)
+            );
+        }
+    }
+
+    #[test]
+    fn test_intersect_both_halves() {
+        let cone = Cone::new();
+
+        let ray = Ray::new(point!(0, 0.5, -5), vector!(0, 0.5, 1).normalize());
+        let xs = cone.local_intersect(&ray);
+        assert_eq!(2, xs.count());
+        assert!(xs.0.iter().all(|i| ray.position(i.t).1 > 0.0));
+
+        let ray = Ray::new(point!(0, -5, 0.5), vector!(0, 1, 0));
+        let xs = cone.local_intersect(&ray);
+        assert_eq!(2, xs.count());
+        assert!(ray.position(xs.0[0].t).1 < 0.0);
+        assert!(ray.position(xs.0[1].t).1 > 0.0);
+    }
+
+    #[test]
+    fn test_intersect_parallel() {
+        let cone = Cone::new();
+
+        let ray = Ray::new(point!(0, 0, -1), vector!(0, 1, 1).normalize());
+        assert_eq!(
+            Intersections::from(&[0.35355], &cone),
+            cone.local_intersect(&ray)
+        );
+    }
+
+    #[test]
+    fn test_intersect_capped() {
+        let cone = Cone {
+            minimum: -0.5,
+            maximum: 0.5,
+            closed: true,
+            ..Cone::new()
+        };
+
+        let cases = [
+            (point!(0, 0, -5), vector!(0, 1, 0), 0),
+            (point!(0, 0, -0.25), vector!(0, 1, 1), 2),
+            (point!(0, 0, -0.25), vector!(0, 1, 0), 4),
+        ];
+
+        for (origin, direction, count) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(count, cone.local_intersect(&ray).count());
+        }
+    }
+
+    #[test]
+    fn test_normal_at() {
+        let cone = Cone::new();
+
+        let cases = [
+            (point!(0, 0, 0), vector!(0, 0, 0)),
+            (point!(1, 1, 1), vector!(1, -SQRT_2, 1)),
+            (point!(-1, -1, 0), vector!(-1, 1, 0)),
+        ];
+
+        for (point, normal) in cases {
+            assert_eq!(normal, cone.local_normal_at(&point));
+        }
+    }
+
+    #[test]
+    fn test_bounds() {
+        let cone = Cone::new();
+        let bounds = cone.bounds();
+        assert_eq!(f64::NEG_INFINITY, bounds.min.0);
+        assert_eq!(f64::INFINITY, bounds.max.2);
+
+        let cone = Cone {
+            minimum: -5.0,
+            maximum: 3.0,
+            ..Cone::new()
+        };
+        assert_eq!(
+            AABB::new(point!(-5, -5, -5), point!(5, 3, 5)),
+            cone.bounds()
+        );
+    }
+}
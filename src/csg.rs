@@ -0,0 +1,291 @@
+use crate::{
+    bounds::AABB,
+    intersections::{Intersection, Intersections},
+    materials::Material,
+    matrices::Matrix,
+    rays::Ray,
+    shapes::{child_normal_at, Shape, Transform},
+    tuples::Tuple,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+// lhit: whether the hit is on the left child, inl/inr: whether the ray is inside each child
+pub fn intersection_allowed(operation: CsgOperation, lhit: bool, inl: bool, inr: bool) -> bool {
+    match operation {
+        CsgOperation::Union => (lhit && !inr) || (!lhit && !inl),
+        CsgOperation::Intersection => (lhit && inr) || (!lhit && inl),
+        CsgOperation::Difference => (lhit && !inr) || (!lhit && inl),
+    }
+}
+
+// like Group, the children are kept in world space
+#[derive(Debug)]
+pub struct Csg {
+    pub operation: CsgOperation,
+    left: Box<dyn Shape>,
+    right: Box<dyn Shape>,
+    transform: Transform,
+    pub material: Material,
+    world_bounds: AABB,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Box<dyn Shape>, right: Box<dyn Shape>) -> Self {
+        let mut csg = Self {
+            operation,
+            left,
+            right,
+            transform: Transform::identity(),
+            material: Material::new(),
+            world_bounds: AABB::empty(),
+        };
+        csg.update_bounds();
+        csg
+    }
+
+    pub fn left(&self) -> &dyn Shape {
+        self.left.as_ref()
+    }
+
+    pub fn right(&self) -> &dyn Shape {
+        self.right.as_ref()
+    }
+
+    // xs must be sorted by t
+    pub fn filter_intersections<'a>(&self, xs: Intersections<'a>) -> Intersections<'a> {
+        let (mut inl, mut inr) = (false, false);
+        let mut result = Vec::new();
+
+        for intersection in xs {
+            let lhit = self.left.includes(intersection.object);
+
+            if intersection_allowed(self.operation, lhit, inl, inr) {
+                result.push(intersection);
+            }
+
+            if lhit {
+                inl = !inl;
+            } else {
+                inr = !inr;
+            }
+        }
+
+        Intersections(result)
+    }
+
+    fn update_bounds(&mut self) {
+        self.world_bounds = AABB::empty();
+
+        for child in [&self.left, &self.right] {
+            self.world_bounds
+                .add_box(&child.bounds().transform(child.transform()));
+        }
+    }
+}
+
+impl Shape for Csg {
+    fn transform(&self) -> &Matrix<4> {
+        self.transform.matrix()
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        // a singular transform has already flattened the children past undoing, so they stay put
+        if let Some(inverse) = self.inverse() {
+            let rebase = transform * inverse;
+
+            for child in [&mut self.left, &mut self.right] {
+                child.set_transform(rebase * *child.transform());
+            }
+        }
+
+        self.transform = Transform::new(transform);
+        self.update_bounds();
+    }
+
+    fn inverse(&self) -> Option<Matrix<4>> {
+        self.transform.inverse()
+    }
+
+    fn inverse_transpose(&self) -> Option<Matrix<4>> {
+        self.transform.inverse_transpose()
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        self.intersect(&ray.transform(self.transform.matrix()))
+    }
+
+    fn intersect(&self, ray: &Ray) -> Intersections<'_> {
+        if !self.world_bounds.intersects(ray) {
+            return Intersections::empty();
+        }
+
+        let mut intersections: Vec<Intersection> = self
+            .left
+            .intersect(ray)
+            .into_iter()
+            .chain(self.right.intersect(ray))
+            .collect();
+
+        intersections.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+        self.filter_intersections(Intersections(intersections))
+    }
+
+    fn divide(&mut self, threshold: usize) {
+        self.left.divide(threshold);
+        self.right.divide(threshold);
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.left.set_material(material.clone());
+        self.right.set_material(material.clone());
+        self.material = material;
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        self.left.includes(other) || self.right.includes(other)
+    }
+
+    // hits always land on a child, which computes its own normal; this is only for direct callers
+    fn local_normal_at(&self, point: &Tuple) -> Tuple {
+        let world_point = *self.transform.matrix() * *point;
+        let children = [self.left.as_ref(), self.right.as_ref()];
+        // there are always two children to choose from
+        let normal = child_normal_at(children.into_iter(), &world_point).unwrap();
+
+        // back into object space, so normal_to_world's inverse transpose cancels out
+        self.transform.matrix().transpose() * normal
+    }
+
+    fn bounds(&self) -> AABB {
+        match self.inverse() {
+            Some(inverse) => self.world_bounds.transform(&inverse),
+            None => AABB::empty(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{cubes::Cube, spheres::Sphere, transformations::*};
+    use std::ptr;
+
+    #[test]
+    fn test_intersection_allowed() {
+        // lhit, inl, inr, union, intersection, difference
+        let cases = [
+            (true, true, true, false, true, false),
+            (true, true, false, true, false, true),
+            (true, false, true, false, true, false),
+            (true, false, false, true, false, true),
+            (false, true, true, false, true, true),
+            (false, true, false, false, true, true),
+            (false, false, true, true, false, false),
+            (false, false, false, true, false, false),
+        ];
+
+        for (lhit, inl, inr, union, intersection, difference) in cases {
+            assert_eq!(
+                union,
+                intersection_allowed(CsgOperation::Union, lhit, inl, inr)
+            );
+            assert_eq!(
+                intersection,
+                intersection_allowed(CsgOperation::Intersection, lhit, inl, inr)
+            );
+            assert_eq!(
+                difference,
+                intersection_allowed(CsgOperation::Difference, lhit, inl, inr)
+            );
+        }
+    }
+
+    #[test]
+    fn test_filter_intersections() {
+        let cases = [
+            (CsgOperation::Union, 0, 3),
+            (CsgOperation::Intersection, 1, 2),
+            (CsgOperation::Difference, 0, 1),
+        ];
+
+        for (operation, x0, x1) in cases {
+            let csg = Csg::new(operation, Box::new(Sphere::new()), Box::new(Cube::new()));
+            let (left, right) = (csg.left(), csg.right());
+
+            let xs = Intersections(vec![
+                Intersection::new(1.0, left),
+                Intersection::new(2.0, right),
+                Intersection::new(3.0, left),
+                Intersection::new(4.0, right),
+            ]);
+            let expected = [xs.0[x0], xs.0[x1]];
+
+            let result = csg.filter_intersections(xs);
+            assert_eq!(2, result.count());
+            assert_eq!(expected[0], result.0[0]);
+            assert_eq!(expected[1], result.0[1]);
+        }
+    }
+
+    #[test]
+    fn test_intersect() {
+        let csg = Csg::new(
+            CsgOperation::Union,
+            Box::new(Sphere::new()),
+            Box::new(Cube::new()),
+        );
+        let ray = Ray::new(point!(0, 2, -5), vector!(0, 0, 1));
+        assert_eq!(0, csg.intersect(&ray).count());
+
+        let mut right = Sphere::new();
+        right.set_transform(translation(0.0, 0.0, 0.5));
+        let mut csg = Csg::new(
+            CsgOperation::Union,
+            Box::new(Sphere::new()),
+            Box::new(right),
+        );
+        csg.set_transform(translation(1.0, 0.0, 0.0));
+
+        let ray = Ray::new(point!(1, 0, -5), vector!(0, 0, 1));
+        let xs = csg.intersect(&ray);
+
+        assert_eq!(2, xs.count());
+        assert_eq!(4.0, xs.0[0].t);
+        assert!(ptr::addr_eq(csg.left(), xs.0[0].object));
+        assert_eq!(6.5, xs.0[1].t);
+        assert!(ptr::addr_eq(csg.right(), xs.0[1].object));
+
+        let hit = xs.hit().unwrap();
+        assert_eq!(vector!(0, 0, -1), hit.object.normal_at(&point!(1, 0, -1)));
+    }
+    #[test]
+    fn test_normal_at() {
+        let mut right = Sphere::new();
+        right.set_transform(translation(3.0, 0.0, 0.0));
+        let mut csg = Csg::new(
+            CsgOperation::Union,
+            Box::new(Sphere::new()),
+            Box::new(right),
+        );
+        csg.set_transform(translation(0.0, 1.0, 0.0) * scaling(2.0, 2.0, 2.0));
+
+        let shape: &dyn Shape = &csg;
+        assert_eq!(vector!(-1, 0, 0), shape.normal_at(&point!(-2, 1, 0)));
+        assert_eq!(vector!(1, 0, 0), shape.normal_at(&point!(8, 1, 0)));
+    }
+}
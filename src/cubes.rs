@@ -0,0 +1,176 @@
+use crate::{
+    bounds::AABB,
+    constants::EPSILON,
+    intersections::Intersections,
+    materials::Material,
+    matrices::Matrix,
+    rays::Ray,
+    shapes::{Shape, Transform},
+    tuples::Tuple,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cube {
+    transform: Transform,
+    pub material: Material,
+}
+
+impl Cube {
+    pub const fn new() -> Self {
+        Self {
+            transform: Transform::identity(),
+            material: Material::new(),
+        }
+    }
+}
+
+impl Default for Cube {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn check_axis(origin: f64, direction: f64) -> (f64, f64) {
+    let tmin_numerator = -1.0 - origin;
+    let tmax_numerator = 1.0 - origin;
+
+    let (tmin, tmax) = if direction.abs() >= EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (
+            tmin_numerator * f64::INFINITY,
+            tmax_numerator * f64::INFINITY,
+        )
+    };
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+impl Shape for Cube {
+    fn transform(&self) -> &Matrix<4> {
+        self.transform.matrix()
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = Transform::new(transform);
+    }
+
+    fn inverse(&self) -> Option<Matrix<4>> {
+        self.transform.inverse()
+    }
+
+    fn inverse_transpose(&self) -> Option<Matrix<4>> {
+        self.transform.inverse_transpose()
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let (xtmin, xtmax) = check_axis(ray.origin.0, ray.direction.0);
+        let (ytmin, ytmax) = check_axis(ray.origin.1, ray.direction.1);
+        let (ztmin, ztmax) = check_axis(ray.origin.2, ray.direction.2);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        if tmin > tmax {
+            return Intersections::empty();
+        }
+
+        Intersections::from(&[tmin, tmax], self)
+    }
+
+    fn local_normal_at(&self, point: &Tuple) -> Tuple {
+        let (x, y, z) = (point.0.abs(), point.1.abs(), point.2.abs());
+        let maxc = x.max(y).max(z);
+
+        if maxc == x {
+            vector!(point.0, 0, 0)
+        } else if maxc == y {
+            vector!(0, point.1, 0)
+        } else {
+            vector!(0, 0, point.2)
+        }
+    }
+
+    fn bounds(&self) -> AABB {
+        AABB::new(point!(-1, -1, -1), point!(1, 1, 1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_intersect() {
+        let cube = Cube::new();
+
+        let cases = [
+            (point!(5, 0.5, 0), vector!(-1, 0, 0), 4.0, 6.0),
+            (point!(-5, 0.5, 0), vector!(1, 0, 0), 4.0, 6.0),
+            (point!(0.5, 5, 0), vector!(0, -1, 0), 4.0, 6.0),
+            (point!(0.5, -5, 0), vector!(0, 1, 0), 4.0, 6.0),
+            (point!(0.5, 0, 5), vector!(0, 0, -1), 4.0, 6.0),
+            (point!(0.5, 0, -5), vector!(0, 0, 1), 4.0, 6.0),
+            (point!(0, 0.5, 0), vector!(0, 0, 1), -1.0, 1.0),
+        ];
+
+        for (origin, direction, t1, t2) in cases {
+            let ray = Ray::new(origin, direction);
+            assert_eq!(
+                Intersections::from(&[t1, t2], &cube),
+                cube.local_intersect(&ray)
+            );
+        }
+    }
+
+    #[test]
+    fn test_intersect_miss() {
+        let cube = Cube::new();
+
+        let cases = [
+            (point!(-2, 0, 0), vector!(0.2673, 0.5345, 0.8018)),
+            (point!(0, -2, 0), vector!(0.8018, 0.2673, 0.5345)),
+            (point!(0, 0, -2), vector!(0.5345, 0.8018, 0.2673)),
+            (point!(2, 0, 2), vector!(0, 0, -1)),
+            (point!(0, 2, 2), vector!(0, -1, 0)),
+            (point!(2, 2, 0), vector!(-1, 0, 0)),
+        ];
+
+        for (origin, direction) in cases {
+            let ray = Ray::new(origin, direction);
+            assert_eq!(0, cube.local_intersect(&ray).count());
+        }
+    }
+
+    #[test]
+    fn test_normal_at() {
+        let cube = Cube::new();
+
+        let cases = [
+            (point!(1, 0.5, -0.8), vector!(1, 0, 0)),
+            (point!(-1, -0.2, 0.9), vector!(-1, 0, 0)),
+            (point!(-0.4, 1, -0.1), vector!(0, 1, 0)),
+            (point!(0.3, -1, -0.7), vector!(0, -1, 0)),
+            (point!(-0.6, 0.3, 1), vector!(0, 0, 1)),
+            (point!(0.4, 0.4, -1), vector!(0, 0, -1)),
+            (point!(1, 1, 1), vector!(1, 0, 0)),
+            (point!(-1, -1, -1), vector!(-1, 0, 0)),
+        ];
+
+        for (point, normal) in cases {
+            assert_eq!(normal, cube.local_normal_at(&point));
+        }
+    }
+}
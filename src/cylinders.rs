@@ -0,0 +1,280 @@
+use crate::{
+    bounds::AABB,
+    constants::EPSILON,
+    intersections::Intersections,
+    materials::Material,
+    matrices::Matrix,
+    rays::Ray,
+    shapes::{Shape, Transform},
+    tuples::Tuple,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cylinder {
+    transform: Transform,
+    pub material: Material,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Cylinder {
+    pub const fn new() -> Self {
+        Self {
+            transform: Transform::identity(),
+            material: Material::new(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+
+    // whether the ray at t is within the unit radius of a cap
+    fn check_cap(ray: &Ray, t: f64) -> bool {
+        let x = ray.origin.0 + t * ray.direction.0;
+        let z = ray.origin.2 + t * ray.direction.2;
+
+        x.powi(2) + z.powi(2) <= 1.0
+    }
+
+    fn intersect_caps(&self, ray: &Ray, ts: &mut Vec<f64>) {
+        if !self.closed || ray.direction.1.abs() < EPSILON {
+            return;
+        }
+
+        for y in [self.minimum, self.maximum] {
+            let t = (y - ray.origin.1) / ray.direction.1;
+            if Self::check_cap(ray, t) {
+                ts.push(t);
+            }
+        }
+    }
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for Cylinder {
+    fn transform(&self) -> &Matrix<4> {
+        self.transform.matrix()
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = Transform::new(transform);
+    }
+
+    fn inverse(&self) -> Option<Matrix<4>> {
+        self.transform.inverse()
+    }
+
+    fn inverse_transpose(&self) -> Option<Matrix<4>> {
+        self.transform.inverse_transpose()
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let mut ts = Vec::with_capacity(4);
+
+        let a = ray.direction.0.powi(2) + ray.direction.2.powi(2);
+
+        if a.abs() >= EPSILON {
+            let b = 2.0 * ray.origin.0 * ray.direction.0 + 2.0 * ray.origin.2 * ray.direction.2;
+            let c = ray.origin.0.powi(2) + ray.origin.2.powi(2) - 1.0;
+
+            let discriminant = b.powi(2) - 4.0 * a * c;
+
+            if discriminant < 0.0 {
+                return Intersections::empty();
+            }
+
+            let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+            let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+
+            for t in [t0.min(t1), t0.max(t1)] {
+                let y = ray.origin.1 + t * ray.direction.1;
+                if self.minimum < y && y < self.maximum {
+                    ts.push(t);
+                }
+            }
+        }
+
+        self.intersect_caps(ray, &mut ts);
+        ts.sort_by(f64::total_cmp);
+
+        Intersections::from(&ts, self)
+    }
+
+    fn local_normal_at(&self, point: &Tuple) -> Tuple {
+        let dist = point.0.powi(2) + point.2.powi(2);
+
+        if dist < 1.0 && point.1 >= self.maximum - EPSILON {
+            vector!(0, 1, 0)
+        } else if dist < 1.0 && point.1 <= self.minimum + EPSILON {
+            vector!(0, -1, 0)
+        } else {
+            vector!(point.0, 0, point.2)
+        }
+    }
+
+    fn bounds(&self) -> AABB {
+        AABB::new(point!(-1, self.minimum, -1), point!(1, self.maximum, 1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_constructor() {
+        let cylinder = Cylinder::new();
+
+        assert_eq!(f64::NEG_INFINITY, cylinder.minimum);
+        assert_eq!(f64::INFINITY, cylinder.maximum);
+        assert!(!cylinder.closed);
+    }
+
+    #[test]
+    fn test_intersect_miss() {
+        let cylinder = Cylinder::new();
+
+        let cases = [
+            (point!(1, 0, 0), vector!(0, 1, 0)),
+            (point!(0, 0, 0), vector!(0, 1, 0)),
+            (point!(0, 0, -5), vector!(1, 1, 1)),
+        ];
+
+        for (origin, direction) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(0, cylinder.local_intersect(&ray).count());
+        }
+    }
+
+    #[test]
+    fn test_intersect() {
+        let cylinder = Cylinder::new();
+
+        let cases = [
+            (point!(1, 0, -5), vector!(0, 0, 1), 5.0, 5.0),
+            (point!(0, 0, -5), vector!(0, 0, 1), 4.0, 6.0),
+            (point!(0.5, 0, -5), vector!(0.1, 1, 1), 6.80798, 7.08872),
+        ];
+
+        for (origin, direction, t0, t1) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(
+                Intersections::from(&[t0, t1], &cylinder),
+                cylinder.local_intersect(&ray)
+            );
+        }
+    }
+
+    #[test]
+    fn test_normal_at() {
+        let cylinder = Cylinder::new();
+
+        let cases = [
+            (point!(1, 0, 0), vector!(1, 0, 0)),
+            (point!(0, 5, -1), vector!(0, 0, -1)),
+            (point!(0, -2, 1), vector!(0, 0, 1)),
+            (point!(-1, 1, 0), vector!(-1, 0, 0)),
+        ];
+
+        for (point, normal) in cases {
+            assert_eq!(normal, cylinder.local_normal_at(&point));
+        }
+    }
+
+    #[test]
+    fn test_intersect_truncated() {
+        let cylinder = Cylinder {
+            minimum: 1.0,
+            maximum: 2.0,
+            ..Cylinder::new()
+        };
+
+        let cases = [
+            (point!(0, 1.5, 0), vector!(0.1, 1, 0), 0),
+            (point!(0, 3, -5), vector!(0, 0, 1), 0),
+            (point!(0, 0, -5), vector!(0, 0, 1), 0),
+            (point!(0, 2, -5), vector!(0, 0, 1), 0),
+            (point!(0, 1, -5), vector!(0, 0, 1), 0),
+            (point!(0, 1.5, -2), vector!(0, 0, 1), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(count, cylinder.local_intersect(&ray).count());
+        }
+    }
+
+    #[test]
+    fn test_intersect_capped() {
+        let cylinder = Cylinder {
+            minimum: 1.0,
+            maximum: 2.0,
+            closed: true,
+            ..Cylinder::new()
+        };
+
+        let cases = [
+            (point!(0, 3, 0), vector!(0, -1, 0), 2),
+            (point!(0, 3, -2), vector!(0, -1, 2), 2),
+            (point!(0, 4, -2), vector!(0, -1, 1), 2),
+            (point!(0, 0, -2), vector!(0, 1, 2), 2),
+            (point!(0, -1, -2), vector!(0, 1, 1), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            let ray = Ray::new(origin, direction.normalize());
+            assert_eq!(count, cylinder.local_intersect(&ray).count());
+        }
+    }
+
+    #[test]
+    fn test_normal_at_caps() {
+        let cylinder = Cylinder {
+            minimum: 1.0,
+            maximum: 2.0,
+            closed: true,
+            ..Cylinder::new()
+        };
+
+        let cases = [
+            (point!(0, 1, 0), vector!(0, -1, 0)),
+            (point!(0.5, 1, 0), vector!(0, -1, 0)),
+            (point!(0, 1, 0.5), vector!(0, -1, 0)),
+            (point!(0, 2, 0), vector!(0, 1, 0)),
+            (point!(0.5, 2, 0), vector!(0, 1, 0)),
+            (point!(0, 2, 0.5), vector!(0, 1, 0)),
+        ];
+
+        for (point, normal) in cases {
+            assert_eq!(normal, cylinder.local_normal_at(&point));
+        }
+    }
+
+    #[test]
+    fn test_bounds() {
+        let cylinder = Cylinder {
+            minimum: -5.0,
+            maximum: 3.0,
+            ..Cylinder::new()
+        };
+
+        assert_eq!(
+            AABB::new(point!(-1, -5, -1), point!(1, 3, 1)),
+            cylinder.bounds()
+        );
+    }
+}
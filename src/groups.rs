@@ -0,0 +1,455 @@
+use crate::{
+    bounds::AABB,
+    intersections::Intersections,
+    materials::Material,
+    matrices::Matrix,
+    rays::Ray,
+    shapes::{child_normal_at, Shape, Transform},
+    tuples::Tuple,
+};
+
+type Children = Vec<Box<dyn Shape>>;
+
+// children are stored in world space: adding a child or transforming the group composes the
+// group's transform onto them, so a child's normal_at already accounts for every parent
+#[derive(Debug)]
+pub struct Group {
+    transform: Transform,
+    pub material: Material,
+    children: Children,
+    world_bounds: AABB,
+}
+
+impl Group {
+    pub const fn new() -> Self {
+        Self {
+            transform: Transform::identity(),
+            material: Material::new(),
+            children: Vec::new(),
+            world_bounds: AABB::empty(),
+        }
+    }
+
+    pub fn children(&self) -> &[Box<dyn Shape>] {
+        &self.children
+    }
+
+    pub fn add_child(&mut self, mut child: Box<dyn Shape>) {
+        child.set_transform(*self.transform.matrix() * *child.transform());
+        self.world_bounds
+            .add_box(&child.bounds().transform(child.transform()));
+        self.children.push(child);
+    }
+
+    // moves each child that fits entirely within one half of its siblings' bounds into that half,
+    // leaving the rest behind. Unbounded children such as planes always stay behind and are left
+    // out of the split, since halving an infinite box gives NaN
+    fn partition_children(&mut self) -> (Children, Children) {
+        let mut finite_bounds = AABB::empty();
+        for child in &self.children {
+            let bounds = child.bounds().transform(child.transform());
+            if bounds.is_finite() {
+                finite_bounds.add_box(&bounds);
+            }
+        }
+
+        if !finite_bounds.is_finite() {
+            return (vec![], vec![]);
+        }
+
+        let (left_bounds, right_bounds) = split_bounds(&finite_bounds);
+        let (mut left, mut right, mut remaining) = (vec![], vec![], vec![]);
+
+        for child in self.children.drain(..) {
+            let bounds = child.bounds().transform(child.transform());
+
+            if left_bounds.contains_box(&bounds) {
+                left.push(child);
+            } else if right_bounds.contains_box(&bounds) {
+                right.push(child);
+            } else {
+                remaining.push(child);
+            }
+        }
+
+        self.children = remaining;
+
+        (left, right)
+    }
+
+    fn make_subgroup(&mut self, children: Children) {
+        let mut subgroup = Group::new();
+
+        for child in children {
+            subgroup.add_child(child);
+        }
+
+        self.children.push(Box::new(subgroup));
+    }
+
+    fn update_bounds(&mut self) {
+        self.world_bounds = AABB::empty();
+
+        for child in &self.children {
+            self.world_bounds
+                .add_box(&child.bounds().transform(child.transform()));
+        }
+    }
+}
+
+// halves the box across its longest axis
+fn split_bounds(bounds: &AABB) -> (AABB, AABB) {
+    let (dx, dy, dz) = (
+        bounds.max.0 - bounds.min.0,
+        bounds.max.1 - bounds.min.1,
+        bounds.max.2 - bounds.min.2,
+    );
+    let greatest = dx.max(dy).max(dz);
+
+    let (mut mid_min, mut mid_max) = (bounds.min, bounds.max);
+
+    if greatest == dx {
+        mid_min.0 = bounds.min.0 + dx / 2.0;
+        mid_max.0 = mid_min.0;
+    } else if greatest == dy {
+        mid_min.1 = bounds.min.1 + dy / 2.0;
+        mid_max.1 = mid_min.1;
+    } else {
+        mid_min.2 = bounds.min.2 + dz / 2.0;
+        mid_max.2 = mid_min.2;
+    }
+
+    (
+        AABB::new(bounds.min, mid_max),
+        AABB::new(mid_min, bounds.max),
+    )
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for Group {
+    fn transform(&self) -> &Matrix<4> {
+        self.transform.matrix()
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        // a singular transform has already flattened the children past undoing, so they stay put
+        if let Some(inverse) = self.inverse() {
+            let rebase = transform * inverse;
+
+            for child in self.children.iter_mut() {
+                child.set_transform(rebase * *child.transform());
+            }
+        }
+
+        self.transform = Transform::new(transform);
+        self.update_bounds();
+    }
+
+    fn inverse(&self) -> Option<Matrix<4>> {
+        self.transform.inverse()
+    }
+
+    fn inverse_transpose(&self) -> Option<Matrix<4>> {
+        self.transform.inverse_transpose()
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        self.intersect(&ray.transform(self.transform.matrix()))
+    }
+
+    // the children are already in world space, so the ray is passed through untouched
+    fn intersect(&self, ray: &Ray) -> Intersections<'_> {
+        if !self.world_bounds.intersects(ray) {
+            return Intersections::empty();
+        }
+
+        let mut intersections: Vec<_> = self
+            .children
+            .iter()
+            .flat_map(|child| child.intersect(ray))
+            .collect();
+
+        intersections.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+        Intersections(intersections)
+    }
+
+    fn divide(&mut self, threshold: usize) {
+        if threshold <= self.children.len() {
+            let (left, right) = self.partition_children();
+
+            if !left.is_empty() {
+                self.make_subgroup(left);
+            }
+            if !right.is_empty() {
+                self.make_subgroup(right);
+            }
+        }
+
+        for child in self.children.iter_mut() {
+            child.divide(threshold);
+        }
+    }
+
+    fn set_material(&mut self, material: Material) {
+        for child in self.children.iter_mut() {
+            child.set_material(material.clone());
+        }
+
+        self.material = material;
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        self.children.iter().any(|child| child.includes(other))
+    }
+
+    fn local_normal_at(&self, point: &Tuple) -> Tuple {
+        let world_point = *self.transform.matrix() * *point;
+
+        match child_normal_at(
+            self.children.iter().map(|child| child.as_ref()),
+            &world_point,
+        ) {
+            // back into object space, so normal_to_world's inverse transpose cancels out
+            Some(normal) => self.transform.matrix().transpose() * normal,
+            // an empty group answers like a unit sphere at its origin
+            None => *point - point!(0, 0, 0),
+        }
+    }
+
+    fn bounds(&self) -> AABB {
+        match self.inverse() {
+            Some(inverse) => self.world_bounds.transform(&inverse),
+            None => AABB::empty(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{planes::Plane, spheres::Sphere, transformations::*};
+    use std::{f64::consts::PI, ptr};
+
+    #[test]
+    fn test_intersect_empty() {
+        let group = Group::new();
+        let ray = Ray::new(point!(0, 0, 0), vector!(0, 0, 1));
+
+        assert!(group.children().is_empty());
+        assert_eq!(0, group.intersect(&ray).count());
+    }
+
+    #[test]
+    fn test_intersect() {
+        let mut group = Group::new();
+        group.add_child(Box::new(Sphere::new()));
+
+        let mut sphere = Sphere::new();
+        sphere.set_transform(translation(0.0, 0.0, -3.0));
+        group.add_child(Box::new(sphere));
+
+        let mut sphere = Sphere::new();
+        sphere.set_transform(translation(5.0, 0.0, 0.0));
+        group.add_child(Box::new(sphere));
+
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+        let xs = group.intersect(&ray);
+        let children = group.children();
+
+        assert_eq!(4, xs.count());
+        assert!(ptr::addr_eq(&*children[1], xs.0[0].object));
+        assert!(ptr::addr_eq(&*children[1], xs.0[1].object));
+        assert!(ptr::addr_eq(&*children[0], xs.0[2].object));
+        assert!(ptr::addr_eq(&*children[0], xs.0[3].object));
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut sphere = Sphere::new();
+        sphere.set_transform(translation(5.0, 0.0, 0.0));
+
+        let mut group = Group::new();
+        group.add_child(Box::new(sphere));
+        group.set_transform(scaling(2.0, 2.0, 2.0));
+
+        let ray = Ray::new(point!(10, 0, -10), vector!(0, 0, 1));
+        assert_eq!(2, group.intersect(&ray).count());
+
+        let ray = Ray::new(point!(5, 0, -10), vector!(0, 0, 1));
+        assert_eq!(0, group.intersect(&ray).count());
+    }
+
+    #[test]
+    fn test_nested_normal_at() {
+        let mut sphere = Sphere::new();
+        sphere.set_transform(translation(5.0, 0.0, 0.0));
+
+        let mut inner = Group::new();
+        inner.set_transform(scaling(1.0, 2.0, 3.0));
+        inner.add_child(Box::new(sphere));
+
+        let mut outer = Group::new();
+        outer.set_transform(rotation(Axis::Y, PI / 2.0));
+        outer.add_child(Box::new(inner));
+
+        let ray = Ray::new(point!(20, 0, -5.5774), vector!(-1, 0, 0));
+        let xs = outer.intersect(&ray);
+        let hit = xs.hit().unwrap();
+
+        assert_eq!(
+            vector!(0.2857, 0.42854, -0.85716),
+            hit.object.normal_at(&point!(1.7321, 1.1547, -5.5774))
+        );
+    }
+
+    #[test]
+    fn test_normal_at() {
+        let mut group = Group::new();
+        for x in [-2.0, 2.0] {
+            let mut sphere = Sphere::new();
+            sphere.set_transform(translation(x, 0.0, 0.0));
+            group.add_child(Box::new(sphere));
+        }
+        group.set_transform(rotation(Axis::Y, PI / 2.0));
+
+        // the sphere at x = 2 now sits at z = -2
+        let shape: &dyn Shape = &group;
+        assert_eq!(vector!(0, 0, -1), shape.normal_at(&point!(0, 0, -3)));
+        assert_eq!(vector!(0, 1, 0), shape.normal_at(&point!(0, 1, 2)));
+
+        let empty: &dyn Shape = &Group::new();
+        assert_eq!(vector!(0, 1, 0), empty.normal_at(&point!(0, 2, 0)));
+    }
+
+    #[test]
+    fn test_set_material() {
+        let mut inner = Group::new();
+        inner.add_child(Box::new(Sphere::new()));
+        let mut group = Group::new();
+        group.add_child(Box::new(inner));
+
+        let mut material = Material::new();
+        material.ambient = 1.0;
+        group.set_material(material.clone());
+
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+        let xs = group.intersect(&ray);
+        assert_eq!(&material, xs.hit().unwrap().object.material());
+        assert_eq!(&material, group.children()[0].material());
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut sphere = Sphere::new();
+        sphere.set_transform(translation(2.0, 5.0, -3.0) * scaling(2.0, 2.0, 2.0));
+
+        let mut group = Group::new();
+        group.add_child(Box::new(sphere));
+        group.add_child(Box::new(Sphere::new()));
+
+        assert_eq!(
+            AABB::new(point!(-1, -1, -5), point!(4, 7, 1)),
+            group.bounds()
+        );
+    }
+
+    #[test]
+    fn test_divide() {
+        let mut group = Group::new();
+        for x in [-2.0, 2.0, 0.0] {
+            let mut sphere = Sphere::new();
+            sphere.set_transform(translation(x, 0.0, 0.0));
+            group.add_child(Box::new(sphere));
+        }
+
+        group.divide(1);
+
+        // the middle sphere straddles the split and stays put
+        let children = group.children();
+        assert_eq!(3, children.len());
+        assert_eq!(
+            AABB::new(point!(-1, -1, -1), point!(1, 1, 1)),
+            children[0].bounds()
+        );
+        assert_eq!(
+            AABB::new(point!(-3, -1, -1), point!(-1, 1, 1)),
+            children[1].bounds()
+        );
+        assert_eq!(
+            AABB::new(point!(1, -1, -1), point!(3, 1, 1)),
+            children[2].bounds()
+        );
+    }
+
+    #[test]
+    fn test_divide_with_plane() {
+        let mut group = Group::new();
+        for x in [-2.0, 2.0, 0.0] {
+            let mut sphere = Sphere::new();
+            sphere.set_transform(translation(x, 0.0, 0.0));
+            group.add_child(Box::new(sphere));
+        }
+        group.add_child(Box::new(Plane::new()));
+
+        group.divide(1);
+
+        // the plane stays put and the spheres split as if it weren't there
+        let children = group.children();
+        assert_eq!(4, children.len());
+        assert!(!children[1].bounds().is_finite());
+        assert_eq!(
+            AABB::new(point!(-3, -1, -1), point!(-1, 1, 1)),
+            children[2].bounds()
+        );
+        assert_eq!(
+            AABB::new(point!(1, -1, -1), point!(3, 1, 1)),
+            children[3].bounds()
+        );
+    }
+
+    #[test]
+    fn test_divide_parity() {
+        let build = || {
+            let mut group = Group::new();
+            for i in 0..64 {
+                let (x, y, z) = ((i % 4) as f64, (i / 4 % 4) as f64, (i / 16) as f64);
+                let mut sphere = Sphere::new();
+                sphere.set_transform(translation(x * 3.0, y * 3.0, z * 3.0));
+                group.add_child(Box::new(sphere));
+            }
+            group.set_transform(rotation(Axis::Y, PI / 6.0));
+            group
+        };
+
+        let undivided = build();
+        let mut divided = build();
+        divided.divide(4);
+        assert!(divided.children().len() < undivided.children().len());
+
+        for i in 0..20 {
+            for j in 0..20 {
+                let target = point!(i as f64 * 0.6 - 1.0, j as f64 * 0.6 - 1.0, 5);
+                let origin = point!(4, 4, -20);
+                let ray = Ray::new(origin, (target - origin).normalize());
+
+                let expected: Vec<_> = undivided.intersect(&ray).into_iter().map(|x| x.t).collect();
+                let actual: Vec<_> = divided.intersect(&ray).into_iter().map(|x| x.t).collect();
+                assert_eq!(expected, actual);
+            }
+        }
+    }
+}
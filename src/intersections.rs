@@ -1,32 +1,142 @@
-use crate::{constants::EPSILON, spheres::*};
+use crate::{constants::EPSILON, rays::Ray, shapes::Shape, tuples::Tuple};
+use std::ptr;
 
-#[derive(Debug)]
-pub struct Intersection {
+#[derive(Debug, Copy, Clone)]
+pub struct Intersection<'a> {
     pub t: f64,
-    pub object: Sphere,
+    pub object: &'a dyn Shape,
+    // where on a triangle the hit landed, for interpolating normals
+    pub uv: Option<(f64, f64)>,
 }
 
-impl Intersection {
-    pub const fn new(t: f64, object: Sphere) -> Self {
-        Self { t, object }
+impl<'a> Intersection<'a> {
+    pub const fn new(t: f64, object: &'a dyn Shape) -> Self {
+        Self {
+            t,
+            object,
+            uv: None,
+        }
     }
+
+    pub const fn with_uv(t: f64, object: &'a dyn Shape, u: f64, v: f64) -> Self {
+        Self {
+            t,
+            object,
+            uv: Some((u, v)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Computations<'a> {
+    pub t: f64,
+    pub object: &'a dyn Shape,
+    pub point: Tuple,
+    pub eyev: Tuple,
+    pub normalv: Tuple,
+    pub inside: bool,
+    pub over_point: Tuple,
+    pub under_point: Tuple,
+    pub reflectv: Tuple,
+    pub n1: f64,
+    pub n2: f64,
+}
+
+impl<'a> Intersection<'a> {
+    pub fn prepare_computations(&self, ray: &Ray, xs: &Intersections<'a>) -> Computations<'a> {
+        let point = ray.position(self.t);
+        let eyev = -ray.direction;
+        let mut normalv = self.object.normal_at_hit(&point, self);
+
+        let inside = normalv.dot_product(eyev) < 0.0;
+        if inside {
+            normalv = -normalv;
+        }
+
+        let (n1, n2) = self.refractive_indices(xs);
+
+        Computations {
+            t: self.t,
+            object: self.object,
+            point,
+            eyev,
+            normalv,
+            inside,
+            over_point: point + normalv * EPSILON,
+            under_point: point - normalv * EPSILON,
+            reflectv: ray.direction.reflect(normalv),
+            n1,
+            n2,
+        }
+    }
+
+    // walks the intersections up to this one, tracking which objects the ray is inside of
+    fn refractive_indices(&self, xs: &Intersections<'a>) -> (f64, f64) {
+        let mut containers: Vec<&dyn Shape> = Vec::new();
+        let index_of = |containers: &[&dyn Shape]| {
+            containers
+                .last()
+                .map_or(1.0, |object| object.material().refractive_index)
+        };
+
+        for intersection in xs {
+            let is_hit = intersection == self;
+            let n1 = index_of(&containers);
+
+            match containers
+                .iter()
+                .position(|object| ptr::addr_eq(*object, intersection.object))
+            {
+                Some(position) => {
+                    containers.remove(position);
+                }
+                None => containers.push(intersection.object),
+            }
+
+            if is_hit {
+                return (n1, index_of(&containers));
+            }
+        }
+
+        (1.0, 1.0)
+    }
+}
+
+pub fn schlick(comps: &Computations) -> f64 {
+    let mut cos = comps.eyev.dot_product(comps.normalv);
+
+    if comps.n1 > comps.n2 {
+        let n = comps.n1 / comps.n2;
+        let sin2_t = n.powi(2) * (1.0 - cos.powi(2));
+
+        // total internal reflection
+        if sin2_t > 1.0 {
+            return 1.0;
+        }
+
+        cos = (1.0 - sin2_t).sqrt();
+    }
+
+    let r0 = ((comps.n1 - comps.n2) / (comps.n1 + comps.n2)).powi(2);
+
+    r0 + (1.0 - r0) * (1.0 - cos).powi(5)
 }
 
-impl PartialEq for Intersection {
+impl PartialEq for Intersection<'_> {
     fn eq(&self, other: &Self) -> bool {
-        self.object == other.object && (self.t - other.t).abs() < EPSILON
+        ptr::addr_eq(self.object, other.object) && (self.t - other.t).abs() < EPSILON
     }
 }
 
 #[derive(Debug, PartialEq)]
-pub struct Intersections(pub Vec<Intersection>);
+pub struct Intersections<'a>(pub Vec<Intersection<'a>>);
 
-impl Intersections {
+impl<'a> Intersections<'a> {
     pub const fn empty() -> Self {
         Self(Vec::new())
     }
 
-    pub fn from(ts: &[f64], object: Sphere) -> Self {
+    pub fn from(ts: &[f64], object: &'a dyn Shape) -> Self {
         Self(ts.iter().map(|t| Intersection::new(*t, object)).collect())
     }
 
@@ -34,7 +144,33 @@ impl Intersections {
         self.0.len()
     }
 
-    pub fn hit(&self) -> Option<&Intersection> {
+    pub fn filter_positive(mut self) -> Self {
+        self.0.retain(|intersection| intersection.t >= 0.0);
+        self
+    }
+
+    pub fn dedup(&mut self) {
+        self.0.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+        // hits on different objects at the same t interleave, so a duplicate needn't be adjacent:
+        // check everything kept within EPSILON behind it
+        let mut kept: Vec<Intersection<'a>> = Vec::with_capacity(self.0.len());
+        for intersection in self.0.drain(..) {
+            let duplicate = kept
+                .iter()
+                .rev()
+                .take_while(|other| intersection.t - other.t < EPSILON)
+                .any(|other| *other == intersection);
+
+            if !duplicate {
+                kept.push(intersection);
+            }
+        }
+
+        self.0 = kept;
+    }
+
+    pub fn hit(&self) -> Option<&Intersection<'a>> {
         self.0.iter().fold(None, |acc, intersection| {
             if intersection.t < 0.0 {
                 return acc;
@@ -47,35 +183,230 @@ impl Intersections {
             }
         })
     }
+
+    // unlike hit, this also considers intersections behind the ray's origin
+    pub fn hit_any(&self) -> Option<&Intersection<'a>> {
+        self.0.iter().min_by(|a, b| a.t.abs().total_cmp(&b.t.abs()))
+    }
+}
+
+impl<'a> IntoIterator for Intersections<'a> {
+    type Item = Intersection<'a>;
+    type IntoIter = std::vec::IntoIter<Intersection<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'b Intersections<'a> {
+    type Item = &'b Intersection<'a>;
+    type IntoIter = std::slice::Iter<'b, Intersection<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a> FromIterator<Intersection<'a>> for Intersections<'a> {
+    fn from_iter<I: IntoIterator<Item = Intersection<'a>>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{planes::Plane, spheres::Sphere, transformations::*};
+    use std::f64::consts::FRAC_1_SQRT_2;
 
     #[test]
     fn test_intersection_constructor() {
         let sphere = Sphere::new();
-        let intersection = Intersection::new(3.5, sphere);
+        let intersection = Intersection::new(3.5, &sphere);
 
         assert_eq!(3.5, intersection.t);
-        assert_eq!(sphere, intersection.object);
+        assert!(ptr::addr_eq(&sphere, intersection.object));
     }
 
     #[test]
     fn test_hit() {
         let sphere = Sphere::new();
 
-        let xs = Intersections::from(&[2.0, 1.0], sphere);
-        assert_eq!(Intersection::new(1.0, sphere), *xs.hit().unwrap());
+        let xs = Intersections::from(&[2.0, 1.0], &sphere);
+        assert_eq!(Intersection::new(1.0, &sphere), *xs.hit().unwrap());
 
-        let xs = Intersections::from(&[1.0, -1.0], sphere);
-        assert_eq!(Intersection::new(1.0, sphere), *xs.hit().unwrap());
+        let xs = Intersections::from(&[1.0, -1.0], &sphere);
+        assert_eq!(Intersection::new(1.0, &sphere), *xs.hit().unwrap());
 
-        let xs = Intersections::from(&[-1.0, -2.0], sphere);
+        let xs = Intersections::from(&[-1.0, -2.0], &sphere);
         assert_eq!(None, xs.hit());
 
-        let xs = Intersections::from(&[5.0, 7.0, -3.0, 2.0], sphere);
-        assert_eq!(Intersection::new(2.0, sphere), *xs.hit().unwrap());
+        let xs = Intersections::from(&[5.0, 7.0, -3.0, 2.0], &sphere);
+        assert_eq!(Intersection::new(2.0, &sphere), *xs.hit().unwrap());
+    }
+
+    #[test]
+    fn test_prepare_computations() {
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+        let shape = Sphere::new();
+        let xs = Intersections::from(&[4.0], &shape);
+        let comps = xs.0[0].prepare_computations(&ray, &xs);
+
+        assert_eq!(4.0, comps.t);
+        assert!(ptr::addr_eq(&shape, comps.object));
+        assert_eq!(point!(0, 0, -1), comps.point);
+        assert_eq!(vector!(0, 0, -1), comps.eyev);
+        assert_eq!(vector!(0, 0, -1), comps.normalv);
+        assert!(!comps.inside);
+
+        let ray = Ray::new(point!(0, 0, 0), vector!(0, 0, 1));
+        let xs = Intersections::from(&[1.0], &shape);
+        let comps = xs.0[0].prepare_computations(&ray, &xs);
+
+        assert_eq!(point!(0, 0, 1), comps.point);
+        assert_eq!(vector!(0, 0, -1), comps.eyev);
+        assert_eq!(vector!(0, 0, -1), comps.normalv);
+        assert!(comps.inside);
+    }
+
+    #[test]
+    fn test_prepare_computations_offsets() {
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+        let mut shape = Sphere::glass();
+        shape.set_transform(translation(0.0, 0.0, 1.0));
+        let xs = Intersections::from(&[5.0], &shape);
+        let comps = xs.0[0].prepare_computations(&ray, &xs);
+
+        assert!(comps.over_point.2 < -EPSILON / 2.0);
+        assert!(comps.point.2 > comps.over_point.2);
+        assert!(comps.under_point.2 > EPSILON / 2.0);
+        assert!(comps.point.2 < comps.under_point.2);
+    }
+
+    #[test]
+    fn test_prepare_computations_reflectv() {
+        let shape = Plane::new();
+        let ray = Ray::new(point!(0, 1, -1), vector!(0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2));
+        let xs = Intersections::from(&[2f64.sqrt()], &shape);
+        let comps = xs.0[0].prepare_computations(&ray, &xs);
+
+        assert_eq!(vector!(0, FRAC_1_SQRT_2, FRAC_1_SQRT_2), comps.reflectv);
+    }
+
+    #[test]
+    fn test_refractive_indices() {
+        let mut a = Sphere::glass();
+        a.set_transform(scaling(2.0, 2.0, 2.0));
+        a.material.refractive_index = 1.5;
+        let mut b = Sphere::glass();
+        b.set_transform(translation(0.0, 0.0, -0.25));
+        b.material.refractive_index = 2.0;
+        let mut c = Sphere::glass();
+        c.set_transform(translation(0.0, 0.0, 0.25));
+        c.material.refractive_index = 2.5;
+
+        let ray = Ray::new(point!(0, 0, -4), vector!(0, 0, 1));
+        let xs = Intersections(vec![
+            Intersection::new(2.0, &a),
+            Intersection::new(2.75, &b),
+            Intersection::new(3.25, &c),
+            Intersection::new(4.75, &b),
+            Intersection::new(5.25, &c),
+            Intersection::new(6.0, &a),
+        ]);
+
+        let expected = [
+            (1.0, 1.5),
+            (1.5, 2.0),
+            (2.0, 2.5),
+            (2.5, 2.5),
+            (2.5, 1.5),
+            (1.5, 1.0),
+        ];
+
+        for (intersection, (n1, n2)) in xs.0.iter().zip(expected) {
+            let comps = intersection.prepare_computations(&ray, &xs);
+            assert_eq!((n1, n2), (comps.n1, comps.n2));
+        }
+    }
+
+    #[test]
+    fn test_schlick() {
+        let shape = Sphere::glass();
+
+        let ray = Ray::new(point!(0, 0, FRAC_1_SQRT_2), vector!(0, 1, 0));
+        let xs = Intersections::from(&[-FRAC_1_SQRT_2, FRAC_1_SQRT_2], &shape);
+        let comps = xs.0[1].prepare_computations(&ray, &xs);
+        assert_eq!(1.0, schlick(&comps));
+
+        let ray = Ray::new(point!(0, 0, 0), vector!(0, 1, 0));
+        let xs = Intersections::from(&[-1.0, 1.0], &shape);
+        let comps = xs.0[1].prepare_computations(&ray, &xs);
+        assert!((0.04 - schlick(&comps)).abs() < EPSILON);
+
+        let ray = Ray::new(point!(0, 0.99, -2), vector!(0, 0, 1));
+        let xs = Intersections::from(&[1.8589], &shape);
+        let comps = xs.0[0].prepare_computations(&ray, &xs);
+        assert!((0.48873 - schlick(&comps)).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_hit_any() {
+        let sphere = Sphere::new();
+        let ray = Ray::new(point!(0, 0, -0.5), vector!(0, 0, 1));
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(Intersection::new(-0.5, &sphere), *xs.hit_any().unwrap());
+        assert_eq!(Intersection::new(1.5, &sphere), *xs.hit().unwrap());
+
+        let xs = Intersections::from(&[5.0, -3.0, 2.0], &sphere);
+        assert_eq!(Intersection::new(2.0, &sphere), *xs.hit_any().unwrap());
+
+        assert_eq!(None, Intersections::empty().hit_any());
+    }
+
+    #[test]
+    fn test_dedup() {
+        let sphere = Sphere::new();
+
+        let mut xs = Intersections::from(&[2.0, 1.0, 2.0 + EPSILON / 2.0, 1.0], &sphere);
+        xs.dedup();
+        assert_eq!(Intersections::from(&[1.0, 2.0], &sphere), xs);
+
+        let mut xs = Intersections(vec![
+            Intersection::new(3.0, &sphere),
+            Intersection::new(1.0, &sphere),
+            Intersection::new(2.0, &sphere),
+        ]);
+        xs.dedup();
+        assert_eq!(Intersections::from(&[1.0, 2.0, 3.0], &sphere), xs);
+
+        let other = Sphere::new();
+        let mut xs = Intersections(vec![
+            Intersection::new(1.0, &sphere),
+            Intersection::new(1.0, &other),
+            Intersection::new(1.0, &sphere),
+            Intersection::new(1.0 + EPSILON / 2.0, &other),
+        ]);
+        xs.dedup();
+        assert_eq!(2, xs.count());
+        assert!(ptr::addr_eq(xs.0[0].object, &sphere));
+        assert!(ptr::addr_eq(xs.0[1].object, &other));
+    }
+
+    #[test]
+    fn test_iterators() {
+        let sphere = Sphere::new();
+        let xs = Intersections::from(&[5.0, -1.0, 7.0, -3.0, 2.0], &sphere);
+
+        let ts: Vec<f64> = (&xs).into_iter().map(|i| i.t).collect();
+        assert_eq!(vec![5.0, -1.0, 7.0, -3.0, 2.0], ts);
+
+        let positive: Intersections = xs.into_iter().filter(|i| i.t >= 0.0).collect();
+        assert_eq!(Intersections::from(&[5.0, 7.0, 2.0], &sphere), positive);
+
+        let xs = Intersections::from(&[5.0, -1.0, 7.0, -3.0, 2.0], &sphere);
+        assert_eq!(positive, xs.filter_positive());
     }
 }
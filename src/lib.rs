@@ -0,0 +1,27 @@
+#[macro_use]
+pub mod tuples;
+
+pub mod bounds;
+pub mod camera;
+pub mod canvas;
+pub mod cones;
+pub mod constants;
+pub mod convert;
+pub mod csg;
+pub mod cubes;
+pub mod cylinders;
+pub mod groups;
+pub mod intersections;
+pub mod lights;
+pub mod materials;
+pub mod matrices;
+pub mod obj;
+pub mod patterns;
+pub mod planes;
+pub mod rays;
+pub mod scene;
+pub mod shapes;
+pub mod spheres;
+pub mod transformations;
+pub mod triangles;
+pub mod world;
@@ -1,8 +1,14 @@
-use crate::tuples::*;
+use crate::{constants::EPSILON, tuples::*, world::World};
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointLight {
     pub intensity: Tuple,
     pub position: Tuple,
+    // the light is divided by constant + linear * d + quadratic * d^2
+    pub constant: f64,
+    pub linear: f64,
+    pub quadratic: f64,
 }
 
 impl PointLight {
@@ -10,6 +16,405 @@ impl PointLight {
         Self {
             position,
             intensity,
+            constant: 1.0,
+            linear: 0.0,
+            quadratic: 0.0,
         }
     }
+
+    // clamped so a light without a constant term doesn't divide by zero right next to it
+    pub fn attenuation(&self, distance: f64) -> f64 {
+        (self.constant + self.linear * distance + self.quadratic * distance * distance).max(EPSILON)
+    }
+
+    // a point light is either visible or it isn't
+    pub fn intensity_at(&self, point: &Tuple, world: &World) -> f64 {
+        if world.is_shadowed(point, &self.position) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Light {
+    Point(PointLight),
+    Area(AreaLight),
+    Spot(Spotlight),
+}
+
+impl Light {
+    pub fn intensity(&self) -> Tuple {
+        match self {
+            Light::Point(light) => light.intensity,
+            Light::Area(light) => light.intensity,
+            Light::Spot(light) => light.intensity,
+        }
+    }
+
+    pub fn position(&self) -> Tuple {
+        match self {
+            Light::Point(light) => light.position,
+            Light::Area(light) => light.position(),
+            Light::Spot(light) => light.position,
+        }
+    }
+
+    // how much the light has dimmed after travelling distance
+    pub fn attenuation(&self, distance: f64) -> f64 {
+        match self {
+            Light::Point(light) => light.attenuation(distance),
+            _ => 1.0,
+        }
+    }
+
+    // the fraction of the light that reaches point
+    pub fn intensity_at(&self, point: &Tuple, world: &World) -> f64 {
+        match self {
+            Light::Point(light) => light.intensity_at(point, world),
+            Light::Area(light) => light.intensity_at(point, world),
+            Light::Spot(light) => light.intensity_at(point, world),
+        }
+    }
+
+    // every position shading should average over
+    pub fn samples(&self) -> impl Iterator<Item = Tuple> + '_ {
+        let (usteps, vsteps) = match self {
+            Light::Area(light) => (light.usteps, light.vsteps),
+            _ => (1, 1),
+        };
+
+        (0..vsteps).flat_map(move |v| {
+            (0..usteps).map(move |u| match self {
+                Light::Area(light) => light.point_on_light(u, v),
+                _ => self.position(),
+            })
+        })
+    }
+}
+
+impl From<PointLight> for Light {
+    fn from(light: PointLight) -> Self {
+        Light::Point(light)
+    }
+}
+
+impl From<AreaLight> for Light {
+    fn from(light: AreaLight) -> Self {
+        Light::Area(light)
+    }
+}
+
+impl From<Spotlight> for Light {
+    fn from(light: Spotlight) -> Self {
+        Light::Spot(light)
+    }
+}
+
+// a rectangle of usteps * vsteps cells, sampled once per cell
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AreaLight {
+    pub corner: Tuple,
+    // the size of a single cell along each edge
+    pub uvec: Tuple,
+    pub vvec: Tuple,
+    pub usteps: usize,
+    pub vsteps: usize,
+    pub intensity: Tuple,
+    pub jitter: bool,
+}
+
+impl AreaLight {
+    pub fn new(
+        corner: Tuple,
+        full_uvec: Tuple,
+        usteps: usize,
+        full_vvec: Tuple,
+        vsteps: usize,
+        intensity: Tuple,
+    ) -> Self {
+        // a light needs at least one cell along each edge
+        let usteps = usteps.max(1);
+        let vsteps = vsteps.max(1);
+
+        Self {
+            corner,
+            uvec: full_uvec / usteps as f64,
+            vvec: full_vvec / vsteps as f64,
+            usteps,
+            vsteps,
+            intensity,
+            jitter: false,
+        }
+    }
+
+    pub fn samples(&self) -> usize {
+        self.usteps * self.vsteps
+    }
+
+    pub fn position(&self) -> Tuple {
+        self.corner
+            + self.uvec * (self.usteps as f64 / 2.0)
+            + self.vvec * (self.vsteps as f64 / 2.0)
+    }
+
+    // the center of cell (u, v), or somewhere inside it when jittering
+    pub fn point_on_light(&self, u: usize, v: usize) -> Tuple {
+        let (du, dv) = if self.jitter {
+            (jitter_offset(u, v, 0), jitter_offset(u, v, 1))
+        } else {
+            (0.5, 0.5)
+        };
+
+        self.corner + self.uvec * (u as f64 + du) + self.vvec * (v as f64 + dv)
+    }
+
+    pub fn intensity_at(&self, point: &Tuple, world: &World) -> f64 {
+        let mut visible = 0;
+
+        for v in 0..self.vsteps {
+            for u in 0..self.usteps {
+                if !world.is_shadowed(point, &self.point_on_light(u, v)) {
+                    visible += 1;
+                }
+            }
+        }
+
+        visible as f64 / self.samples() as f64
+    }
+}
+
+// a point light that only shines within a cone around direction
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spotlight {
+    pub position: Tuple,
+    pub direction: Tuple,
+    // half-angles of the fully lit and the outermost cones, in radians
+    pub inner_angle: f64,
+    pub outer_angle: f64,
+    pub intensity: Tuple,
+}
+
+impl Spotlight {
+    pub fn new(
+        position: Tuple,
+        direction: Tuple,
+        inner_angle: f64,
+        outer_angle: f64,
+        intensity: Tuple,
+    ) -> Self {
+        Self {
+            position,
+            direction: direction.normalize(),
+            inner_angle,
+            outer_angle,
+            intensity,
+        }
+    }
+
+    // 1 inside the inner cone, 0 outside the outer cone and smoothstepped between
+    pub fn falloff(&self, point: &Tuple) -> f64 {
+        let cos_angle = (*point - self.position)
+            .normalize()
+            .dot_product(self.direction);
+        let (cos_inner, cos_outer) = (self.inner_angle.cos(), self.outer_angle.cos());
+
+        if cos_angle >= cos_inner {
+            return 1.0;
+        }
+        if cos_angle <= cos_outer {
+            return 0.0;
+        }
+
+        let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    pub fn intensity_at(&self, point: &Tuple, world: &World) -> f64 {
+        let falloff = self.falloff(point);
+
+        if falloff <= 0.0 || world.is_shadowed(point, &self.position) {
+            0.0
+        } else {
+            falloff
+        }
+    }
+}
+
+// a fixed hash of the cell so renders stay reproducible, in [0, 1)
+pub(crate) fn jitter_offset(u: usize, v: usize, axis: u64) -> f64 {
+    let mut hash = ((u as u64) << 32) ^ ((v as u64) << 1) ^ axis;
+    hash = hash.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash ^= hash >> 32;
+
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_point_light_intensity_at() {
+        let world = World::default();
+        let Light::Point(light) = world.lights[0] else {
+            panic!("expected the default world to have a point light");
+        };
+
+        let cases = [
+            (point!(0, 1.0001, 0), 1.0),
+            (point!(-1.0001, 0, 0), 1.0),
+            (point!(0, 0, -1.0001), 1.0),
+            (point!(0, 0, 1.0001), 0.0),
+            (point!(1.0001, 0, 0), 0.0),
+            (point!(0, -1.0001, 0), 0.0),
+            (point!(0, 0, 0), 0.0),
+        ];
+
+        for (point, intensity) in cases {
+            assert_eq!(intensity, light.intensity_at(&point, &world));
+        }
+    }
+
+    #[test]
+    fn test_area_light() {
+        let light = AreaLight::new(
+            point!(0, 0, 0),
+            vector!(2, 0, 0),
+            4,
+            vector!(0, 0, 1),
+            2,
+            color!(1, 1, 1),
+        );
+
+        assert_eq!(vector!(0.5, 0, 0), light.uvec);
+        assert_eq!(vector!(0, 0, 0.5), light.vvec);
+        assert_eq!(8, light.samples());
+        assert_eq!(point!(1, 0, 0.5), light.position());
+
+        let cases = [
+            (0, 0, point!(0.25, 0, 0.25)),
+            (1, 0, point!(0.75, 0, 0.25)),
+            (0, 1, point!(0.25, 0, 0.75)),
+            (2, 0, point!(1.25, 0, 0.25)),
+            (3, 1, point!(1.75, 0, 0.75)),
+        ];
+
+        for (u, v, point) in cases {
+            assert_eq!(point, light.point_on_light(u, v));
+        }
+
+        let jittered = AreaLight {
+            jitter: true,
+            ..light
+        };
+        for (u, v, _) in cases {
+            let point = jittered.point_on_light(u, v);
+            assert!((u as f64 * 0.5..(u + 1) as f64 * 0.5).contains(&point.0));
+            assert!((v as f64 * 0.5..(v + 1) as f64 * 0.5).contains(&point.2));
+            assert_eq!(point, jittered.point_on_light(u, v));
+        }
+    }
+
+    #[test]
+    fn test_area_light_zero_steps() {
+        let light = AreaLight::new(
+            point!(0, 0, 0),
+            vector!(2, 0, 0),
+            0,
+            vector!(0, 0, 1),
+            0,
+            color!(1, 1, 1),
+        );
+
+        assert_eq!(1, light.samples());
+        assert_eq!(vector!(2, 0, 0), light.uvec);
+        assert_eq!(vector!(0, 0, 1), light.vvec);
+        assert_eq!(point!(1, 0, 0.5), light.position());
+        assert_eq!(point!(1, 0, 0.5), light.point_on_light(0, 0));
+    }
+
+    #[test]
+    fn test_area_light_intensity_at() {
+        let world = World::default();
+        let light = AreaLight::new(
+            point!(-0.5, -0.5, -5),
+            vector!(1, 0, 0),
+            2,
+            vector!(0, 1, 0),
+            2,
+            color!(1, 1, 1),
+        );
+
+        let cases = [
+            (point!(0, 0, 2), 0.0),
+            (point!(1, -1, 2), 0.25),
+            (point!(1.5, 0, 2), 0.5),
+            (point!(1.25, 1.25, 3), 0.75),
+            (point!(0, 0, -2), 1.0),
+        ];
+
+        for (point, intensity) in cases {
+            assert_eq!(intensity, light.intensity_at(&point, &world));
+        }
+    }
+
+    #[test]
+    fn test_samples() {
+        let light = Light::from(PointLight::new(point!(1, 2, 3), color!(1, 1, 1)));
+        assert_eq!(vec![point!(1, 2, 3)], light.samples().collect::<Vec<_>>());
+        assert_eq!(point!(1, 2, 3), light.position());
+
+        let light = Light::from(AreaLight::new(
+            point!(0, 0, 0),
+            vector!(2, 0, 0),
+            2,
+            vector!(0, 0, 2),
+            2,
+            color!(1, 1, 1),
+        ));
+        assert_eq!(
+            vec![
+                point!(0.5, 0, 0.5),
+                point!(1.5, 0, 0.5),
+                point!(0.5, 0, 1.5),
+                point!(1.5, 0, 1.5)
+            ],
+            light.samples().collect::<Vec<_>>()
+        );
+        assert_eq!(point!(1, 0, 1), light.position());
+    }
+
+    #[test]
+    fn test_spotlight_intensity_at() {
+        let world = World::default();
+        let light = Spotlight::new(
+            point!(0, 0, -10),
+            vector!(0, 0, 1),
+            PI / 12.0,
+            PI / 6.0,
+            color!(1, 1, 1),
+        );
+
+        // on the central axis, straight at the outer sphere
+        assert_eq!(1.0, light.intensity_at(&point!(0, 0, -1.0001), &world));
+        // well outside the outer cone
+        assert_eq!(0.0, light.intensity_at(&point!(0, 10, -5), &world));
+
+        // halfway between the two cones in cosine
+        let (cos_inner, cos_outer) = ((PI / 12.0).cos(), (PI / 6.0).cos());
+        let angle = ((cos_inner + cos_outer) / 2.0).acos();
+        let point = point!(0, 5.0 * angle.tan(), -5);
+        assert!((0.5 - light.falloff(&point)).abs() < 1e-9);
+
+        let light = Light::from(light);
+        assert_eq!(vec![point!(0, 0, -10)], light.samples().collect::<Vec<_>>());
+    }
 }
@@ -1,70 +1,70 @@
 use std::{
-    fs,
+    env, fs,
     io::{BufWriter, Write},
 };
 
-pub mod canvas;
-pub mod constants;
-pub mod convert;
-pub mod intersections;
-pub mod lights;
-pub mod materials;
-pub mod matrices;
-pub mod rays;
-pub mod spheres;
-pub mod transformations;
-#[macro_use]
-pub mod tuples;
-
-use canvas::Canvas;
-use lights::PointLight;
-use rays::Ray;
-use spheres::Sphere;
-use tuples::Tuple;
-
-fn main() {
-    let file = fs::File::create("out.ppm").expect("failed to open file");
-    let mut writer = BufWriter::new(file);
-
-    let canvas_pixels = 500;
-    let mut canvas = Canvas::new(canvas_pixels, canvas_pixels);
-
-    let ray_origin = point!(0, 0, -5.0);
-    let wall_z = 10.0;
-    let wall_size = 7.0;
-
-    let pixel_size = wall_size / canvas_pixels as f64;
-    let half = wall_size / 2.0;
+use ray_tracer::{
+    camera::Camera,
+    color,
+    lights::{Light, PointLight},
+    point,
+    scene::parse_scene,
+    spheres::Sphere,
+    transformations::view_transform,
+    vector,
+    world::World,
+};
 
+fn default_scene() -> (Camera, World) {
     let mut sphere = Sphere::new();
     sphere.material.color = color!(0.1, 1, 0.1);
 
-    let light = PointLight::new(point!(-10, 10, -10), color!(1, 1, 1));
+    let mut world = World::new();
+    world.objects.push(Box::new(sphere));
+    world.lights.push(Light::from(PointLight::new(
+        point!(-10, 10, -10),
+        color!(1, 1, 1),
+    )));
 
-    for y in 0..canvas_pixels {
-        let world_y = half - pixel_size * y as f64;
+    let mut camera = Camera::new(500, 500, 2.0 * (3.5f64 / 15.0).atan());
+    camera.set_transform(view_transform(
+        point!(0, 0, -5),
+        point!(0, 0, 0),
+        vector!(0, 1, 0),
+    ));
 
-        for x in 0..canvas_pixels {
-            let world_x = -half + pixel_size * x as f64;
-
-            let position = point!(world_x, world_y, wall_z);
+    (camera, world)
+}
 
-            let ray = Ray::new(ray_origin, (position - ray_origin).normalize());
-            let intersections = sphere.intersect(&ray);
+fn main() {
+    // cargo run -- scene.yml
+    let (camera, world) = match env::args().nth(1) {
+        Some(path) => {
+            let input = fs::read_to_string(path).expect("failed to read scene");
+            parse_scene(&input).expect("failed to parse scene")
+        }
+        None => default_scene(),
+    };
 
-            if let Some(hit) = intersections.hit() {
-                let point = ray.position(hit.t);
-                let normal = hit.object.normal_at(&point);
-                let eye = -ray.direction;
+    let canvas = camera.render_parallel(&world);
 
-                let color = hit.object.material.lighting(&light, &point, &eye, &normal);
-                canvas.write_pixel(x, y, &color);
-            }
-        }
+    #[cfg(feature = "png")]
+    {
+        let file = fs::File::create("out.png").expect("failed to open file");
+        let mut writer = BufWriter::new(file);
+        canvas
+            .write_png(&mut writer)
+            .expect("failed to write png to file");
+        writer.flush().expect("failed to flush buffer");
     }
 
-    canvas
-        .write_out(&mut writer)
-        .expect("failed to write ppm to file");
-    writer.flush().expect("failed to flush buffer");
+    #[cfg(not(feature = "png"))]
+    {
+        let file = fs::File::create("out.ppm").expect("failed to open file");
+        let mut writer = BufWriter::new(file);
+        canvas
+            .write_out(&mut writer)
+            .expect("failed to write ppm to file");
+        writer.flush().expect("failed to flush buffer");
+    }
 }
@@ -1,15 +1,24 @@
-use crate::{
-    lights::PointLight,
-    tuples::{color, Tuple},
-};
+use crate::{constants::EPSILON, lights::Light, patterns::Pattern, shapes::Shape, tuples::Tuple};
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Material {
     pub color: Tuple,
     pub ambient: f64,
     pub diffuse: f64,
     pub specular: f64,
     pub shininess: f64,
+    pub roughness: f64,
+    pub reflective: f64,
+    pub reflection_roughness: f64,
+    // multiplies reflected light, so metals can reflect in their own color
+    pub reflection_tint: Tuple,
+    pub transparency: f64,
+    pub refractive_index: f64,
+    pub toon_bands: Option<usize>,
+    pub pattern: Option<Pattern>,
+    // bit i links light i; None links every light
+    pub light_mask: Option<u64>,
 }
 
 impl Material {
@@ -20,49 +29,193 @@ impl Material {
             diffuse: 0.9,
             specular: 0.9,
             shininess: 200.0,
+            roughness: 0.0,
+            reflective: 0.0,
+            reflection_roughness: 0.0,
+            reflection_tint: color!(1, 1, 1),
+            transparency: 0.0,
+            refractive_index: 1.0,
+            toon_bands: None,
+            pattern: None,
+            light_mask: None,
+        }
+    }
+
+    pub fn from_pbr(base_color: Tuple, metallic: f64, roughness: f64) -> Self {
+        let metallic = metallic.clamp(0.0, 1.0);
+        let roughness = roughness.clamp(0.0, 1.0);
+        let alpha = roughness * roughness;
+
+        Self {
+            color: base_color,
+            diffuse: 0.9 * (1.0 - metallic),
+            specular: (0.5 + 0.5 * metallic) * (1.0 - 0.75 * roughness),
+            shininess: (2.0 / (alpha * alpha).max(EPSILON) - 2.0).clamp(1.0, 1000.0),
+            roughness,
+            // metals mirror their surroundings, tinted by their base color
+            reflective: metallic,
+            reflection_roughness: roughness,
+            reflection_tint: color!(1, 1, 1) * (1.0 - metallic) + base_color * metallic,
+            ..Self::new()
+        }
+    }
+
+    pub fn is_linked_to(&self, light_index: usize) -> bool {
+        match self.light_mask {
+            None => true,
+            Some(mask) => 1u64
+                .checked_shl(light_index as u32)
+                .is_some_and(|bit| mask & bit != 0),
+        }
+    }
+
+    pub fn color_at(&self, object: &dyn Shape, position: &Tuple) -> Tuple {
+        match &self.pattern {
+            Some(pattern) => pattern.pattern_at_shape(object, position),
+            None => self.color,
         }
     }
 
     pub fn lighting(
         &self,
-        light: &PointLight,
+        object: &dyn Shape,
+        light: &Light,
+        position: &Tuple,
+        eyev: &Tuple,
+        normalv: &Tuple,
+        // the fraction of the light reaching position, 0 when fully shadowed
+        intensity: f64,
+    ) -> Tuple {
+        let effective_color = self
+            .color_at(object, position)
+            .hadamard_product(light.intensity());
+
+        effective_color * self.ambient
+            + self.shade(effective_color, light, position, eyev, normalv, intensity)
+    }
+
+    // lighting without the ambient term, for when something else supplies it
+    pub fn direct_lighting(
+        &self,
+        object: &dyn Shape,
+        light: &Light,
         position: &Tuple,
         eyev: &Tuple,
         normalv: &Tuple,
+        intensity: f64,
     ) -> Tuple {
-        let effective_color = self.color.hadamard_product(light.intensity);
+        if intensity <= 0.0 {
+            return color!(0, 0, 0);
+        }
 
-        let lightv = (light.position - *position).normalize();
+        let effective_color = self
+            .color_at(object, position)
+            .hadamard_product(light.intensity());
 
-        let ambient = effective_color * self.ambient;
+        self.shade(effective_color, light, position, eyev, normalv, intensity)
+    }
 
-        let light_dot_normal = lightv.dot_product(*normalv);
+    // the diffuse and specular terms
+    fn shade(
+        &self,
+        effective_color: Tuple,
+        light: &Light,
+        position: &Tuple,
+        eyev: &Tuple,
+        normalv: &Tuple,
+        intensity: f64,
+    ) -> Tuple {
+        if intensity <= 0.0 {
+            return color!(0, 0, 0);
+        }
 
-        let (diffuse, specular) = if light_dot_normal < 0.0 {
-            (color!(0, 0, 0), color!(0, 0, 0))
-        } else {
-            let diffuse = effective_color * self.diffuse * light_dot_normal;
+        // area lights average diffuse and specular over every sample on the light
+        let (mut sum, mut samples) = (color!(0, 0, 0), 0);
+
+        for sample in light.samples() {
+            let to_light = sample - *position;
+            let lightv = to_light.normalize();
+            let attenuation = light.attenuation(to_light.magnitude());
+            let light_dot_normal = lightv.dot_product(*normalv);
+            samples += 1;
+
+            // NaN when the point sits on the light itself, leaving no direction to it
+            if light_dot_normal.is_nan() || light_dot_normal < 0.0 {
+                continue;
+            }
+
+            let mut diffuse_intensity = light_dot_normal * self.oren_nayar(&lightv, eyev, normalv);
+
+            if let Some(bands) = self.toon_bands {
+                let bands = bands.max(1) as f64;
+                diffuse_intensity = (diffuse_intensity * bands).ceil() / bands;
+            }
+
+            sum = sum + effective_color * self.diffuse * diffuse_intensity / attenuation;
 
             let reflect_dot_eye = -lightv.reflect(*normalv).dot_product(*eyev);
 
-            if reflect_dot_eye <= 0.0 {
-                (diffuse, color!(0, 0, 0))
-            } else {
-                (
-                    diffuse,
-                    light.intensity * self.specular * reflect_dot_eye.powf(self.shininess),
-                )
+            if reflect_dot_eye > 0.0 {
+                let mut factor = reflect_dot_eye.powf(self.shininess);
+
+                // toon highlights are either fully on or off
+                if self.toon_bands.is_some() {
+                    factor = if factor > 0.5 { 1.0 } else { 0.0 };
+                }
+
+                sum = sum + light.intensity() * self.specular * factor / attenuation;
             }
+        }
+
+        sum / samples.max(1) as f64 * intensity
+    }
+
+    // scales the lambertian term; a roughness of 0 reduces to plain lambert
+    fn oren_nayar(&self, lightv: &Tuple, eyev: &Tuple, normalv: &Tuple) -> f64 {
+        if self.roughness <= 0.0 {
+            return 1.0;
+        }
+
+        let sigma2 = self.roughness * self.roughness;
+        let a = 1.0 - 0.5 * sigma2 / (sigma2 + 0.33);
+        let b = 0.45 * sigma2 / (sigma2 + 0.09);
+
+        let light_dot_normal = lightv.dot_product(*normalv);
+        let eye_dot_normal = eyev.dot_product(*normalv).clamp(-1.0, 1.0);
+        let theta_i = light_dot_normal.clamp(-1.0, 1.0).acos();
+        let theta_r = eye_dot_normal.acos();
+        let alpha = theta_i.max(theta_r);
+        let beta = theta_i.min(theta_r);
+
+        let light_tangent = *lightv - *normalv * light_dot_normal;
+        let eye_tangent = *eyev - *normalv * eye_dot_normal;
+        let cos_phi = if light_tangent.magnitude() < EPSILON || eye_tangent.magnitude() < EPSILON {
+            0.0
+        } else {
+            light_tangent
+                .normalize()
+                .dot_product(eye_tangent.normalize())
+                .max(0.0)
         };
 
-        ambient + diffuse + specular
+        a + b * cos_phi * alpha.sin() * beta.tan()
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::tuples::*;
+    use crate::{
+        lights::PointLight,
+        patterns::{PatternKind, StripePattern},
+        spheres::Sphere,
+    };
 
     #[test]
     fn test_constructor() {
@@ -73,51 +226,269 @@ mod test {
         assert_eq!(0.9, material.diffuse);
         assert_eq!(0.9, material.specular);
         assert_eq!(200.0, material.shininess);
+        assert_eq!(0.0, material.roughness);
+        assert_eq!(0.0, material.reflective);
+        assert_eq!(0.0, material.reflection_roughness);
+        assert_eq!(color!(1, 1, 1), material.reflection_tint);
+        assert_eq!(0.0, material.transparency);
+        assert_eq!(1.0, material.refractive_index);
+        assert_eq!(None, material.toon_bands);
+        assert_eq!(None, material.pattern);
+        assert_eq!(None, material.light_mask);
     }
 
     #[test]
     fn test_lighting() {
+        let object = Sphere::new();
         let material = Material::new();
         let position = point!(0, 0, 0);
 
         let eyev = vector!(0, 0, -1);
         let normalv = vector!(0, 0, -1);
-        let light = PointLight::new(point!(0, 0, -10), color!(1, 1, 1));
+        let light = Light::from(PointLight::new(point!(0, 0, -10), color!(1, 1, 1)));
         assert_eq!(
             color!(1.9, 1.9, 1.9),
-            material.lighting(&light, &position, &eyev, &normalv)
+            material.lighting(&object, &light, &position, &eyev, &normalv, 1.0)
         );
 
         let eyev = vector!(0, 2f64.sqrt() / 2.0, -2f64.sqrt() / 2.0);
         let normalv = vector!(0, 0, -1);
-        let light = PointLight::new(point!(0, 0, -10), color!(1, 1, 1));
+        let light = Light::from(PointLight::new(point!(0, 0, -10), color!(1, 1, 1)));
         assert_eq!(
             color!(1.0, 1.0, 1.0),
-            material.lighting(&light, &position, &eyev, &normalv)
+            material.lighting(&object, &light, &position, &eyev, &normalv, 1.0)
         );
 
         let eyev = vector!(0, 0, -1);
         let normalv = vector!(0, 0, -1);
-        let light = PointLight::new(point!(0, 10, -10), color!(1, 1, 1));
+        let light = Light::from(PointLight::new(point!(0, 10, -10), color!(1, 1, 1)));
         assert_eq!(
             color!(0.7364, 0.7364, 0.7364),
-            material.lighting(&light, &position, &eyev, &normalv)
+            material.lighting(&object, &light, &position, &eyev, &normalv, 1.0)
         );
 
         let eyev = vector!(0, -2f64.sqrt() / 2.0, -2f64.sqrt() / 2.0);
         let normalv = vector!(0, 0, -1);
-        let light = PointLight::new(point!(0, 10, -10), color!(1, 1, 1));
+        let light = Light::from(PointLight::new(point!(0, 10, -10), color!(1, 1, 1)));
         assert_eq!(
             color!(1.6364, 1.6364, 1.6364),
-            material.lighting(&light, &position, &eyev, &normalv)
+            material.lighting(&object, &light, &position, &eyev, &normalv, 1.0)
+        );
+
+        let eyev = vector!(0, 0, -1);
+        let normalv = vector!(0, 0, -1);
+        let light = Light::from(PointLight::new(point!(0, 0, 10), color!(1, 1, 1)));
+        assert_eq!(
+            color!(0.1, 0.1, 0.1),
+            material.lighting(&object, &light, &position, &eyev, &normalv, 1.0)
         );
+    }
+
+    #[test]
+    fn test_lighting_in_shadow() {
+        let object = Sphere::new();
+        let material = Material::new();
+        let position = point!(0, 0, 0);
 
         let eyev = vector!(0, 0, -1);
         let normalv = vector!(0, 0, -1);
-        let light = PointLight::new(point!(0, 0, 10), color!(1, 1, 1));
+        let light = Light::from(PointLight::new(point!(0, 0, -10), color!(1, 1, 1)));
         assert_eq!(
             color!(0.1, 0.1, 0.1),
-            material.lighting(&light, &position, &eyev, &normalv)
+            material.lighting(&object, &light, &position, &eyev, &normalv, 0.0)
+        );
+        assert_eq!(
+            color!(1.9, 1.9, 1.9),
+            material.lighting(&object, &light, &position, &eyev, &normalv, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_lighting_intensity() {
+        let object = Sphere::new();
+        let material = Material {
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.0,
+            ..Material::new()
+        };
+        let light = Light::from(PointLight::new(point!(0, 0, -10), color!(1, 1, 1)));
+        let (position, eyev, normalv) = (point!(0, 0, -1), vector!(0, 0, -1), vector!(0, 0, -1));
+
+        let cases = [(1.0, 1.0), (0.5, 0.55), (0.0, 0.1)];
+
+        for (intensity, result) in cases {
+            assert_eq!(
+                color!(result, result, result),
+                material.lighting(&object, &light, &position, &eyev, &normalv, intensity)
+            );
+        }
+    }
+
+    #[test]
+    fn test_lighting_attenuation() {
+        let object = Sphere::new();
+        let material = Material {
+            ambient: 0.0,
+            specular: 0.0,
+            ..Material::new()
+        };
+        let light = Light::from(PointLight {
+            constant: 0.0,
+            quadratic: 1.0,
+            ..PointLight::new(point!(0, 0, -3), color!(1, 1, 1))
+        });
+        let (eyev, normalv) = (vector!(0, 0, -1), vector!(0, 0, -1));
+
+        let near = material.lighting(&object, &light, &point!(0, 0, -2), &eyev, &normalv, 1.0);
+        let far = material.lighting(&object, &light, &point!(0, 0, -1), &eyev, &normalv, 1.0);
+
+        // twice as far away receives a quarter of the light
+        assert_eq!(color!(0.9, 0.9, 0.9), near);
+        assert_eq!(near * 0.25, far);
+
+        // no constant term, so nothing keeps the light finite at zero distance
+        let at_light = material.lighting(&object, &light, &point!(0, 0, -3), &eyev, &normalv, 1.0);
+        assert_eq!(color!(0, 0, 0), at_light);
+
+        let unattenuated = Light::from(PointLight {
+            constant: 0.0,
+            ..PointLight::new(point!(0, 0, -3), color!(1, 1, 1))
+        });
+        let color = material.lighting(
+            &object,
+            &unattenuated,
+            &point!(0, 0, -2),
+            &eyev,
+            &normalv,
+            1.0,
+        );
+        assert!(color.0.is_finite() && color.1.is_finite() && color.2.is_finite());
+    }
+
+    #[test]
+    fn test_lighting_oren_nayar() {
+        let object = Sphere::new();
+        let lambert = Material::new();
+        let mut rough = Material::new();
+        rough.roughness = 1.0;
+
+        let position = point!(0, 0, 0);
+        let normalv = vector!(0, 0, -1);
+
+        let grazing = 80f64.to_radians();
+        let eyev = vector!(0, grazing.sin(), -grazing.cos());
+        let light = Light::from(PointLight::new(eyev * 10.0 + position, color!(1, 1, 1)));
+        let lambert = lambert.lighting(&object, &light, &position, &eyev, &normalv, 1.0);
+        let rough = rough.lighting(&object, &light, &position, &eyev, &normalv, 1.0);
+        assert!(rough.0 > lambert.0);
+        assert!(rough.1 > lambert.1);
+        assert!(rough.2 > lambert.2);
+    }
+
+    #[test]
+    fn test_from_pbr() {
+        let object = Sphere::new();
+        let gold = Material::from_pbr(color!(1, 0.8, 0.3), 1.0, 0.0);
+        assert_eq!(color!(1, 0.8, 0.3), gold.color);
+        assert!(gold.diffuse < EPSILON);
+        assert!(gold.specular > Material::new().specular);
+
+        let smooth = Material::from_pbr(color!(1, 1, 1), 0.0, 0.0);
+        let rough = Material::from_pbr(color!(1, 1, 1), 0.0, 1.0);
+        assert!(rough.shininess < smooth.shininess);
+        assert!(rough.specular < smooth.specular);
+
+        let smooth = Material {
+            diffuse: 0.0,
+            ..smooth
+        };
+        let rough = Material {
+            diffuse: 0.0,
+            ..rough
+        };
+        let position = point!(0, 0, 0);
+        let normalv = vector!(0, 0, -1);
+        let light = Light::from(PointLight::new(point!(0, 0, -10), color!(1, 1, 1)));
+
+        let eyev = vector!(0, 0, -1);
+        let peak_smooth = smooth.lighting(&object, &light, &position, &eyev, &normalv, 1.0);
+        let peak_rough = rough.lighting(&object, &light, &position, &eyev, &normalv, 1.0);
+        assert!(peak_rough.0 < peak_smooth.0);
+
+        let eyev = vector!(0, 0.5, -(0.75f64.sqrt()));
+        let off_smooth = smooth.lighting(&object, &light, &position, &eyev, &normalv, 1.0);
+        let off_rough = rough.lighting(&object, &light, &position, &eyev, &normalv, 1.0);
+        assert!(off_rough.0 > off_smooth.0);
+    }
+
+    #[test]
+    fn test_lighting_toon_bands() {
+        let object = Sphere::new();
+        let material = Material {
+            ambient: 0.0,
+            specular: 0.0,
+            toon_bands: Some(3),
+            ..Material::new()
+        };
+        let position = point!(0, 0, 0);
+        let eyev = vector!(0, 0, -1);
+        let light = Light::from(PointLight::new(point!(0, 0, -10), color!(1, 1, 1)));
+
+        let mut values: Vec<f64> = (0..90)
+            .map(|degrees| {
+                let angle = (degrees as f64).to_radians();
+                let normalv = vector!(0, angle.sin(), -angle.cos());
+                material
+                    .lighting(&object, &light, &position, &eyev, &normalv, 1.0)
+                    .0
+            })
+            .collect();
+        values.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+
+        assert_eq!(vec![0.9, 0.6, 0.3], values);
+    }
+
+    #[test]
+    fn test_is_linked_to() {
+        let material = Material::new();
+        assert!(material.is_linked_to(0));
+        assert!(material.is_linked_to(100));
+
+        let material = Material {
+            light_mask: Some(0b101),
+            ..Material::new()
+        };
+        assert!(material.is_linked_to(0));
+        assert!(!material.is_linked_to(1));
+        assert!(material.is_linked_to(2));
+        assert!(!material.is_linked_to(64));
+    }
+
+    #[test]
+    fn test_lighting_pattern() {
+        let object = Sphere::new();
+        let material = Material {
+            pattern: Some(Pattern::new(PatternKind::Stripe(StripePattern::new(
+                color!(1, 1, 1),
+                color!(0, 0, 0),
+            )))),
+            ambient: 1.0,
+            diffuse: 0.0,
+            specular: 0.0,
+            ..Material::new()
+        };
+        let eyev = vector!(0, 0, -1);
+        let normalv = vector!(0, 0, -1);
+        let light = Light::from(PointLight::new(point!(0, 0, -10), color!(1, 1, 1)));
+
+        assert_eq!(
+            color!(1, 1, 1),
+            material.lighting(&object, &light, &point!(0.9, 0, 0), &eyev, &normalv, 1.0)
+        );
+        assert_eq!(
+            color!(0, 0, 0),
+            material.lighting(&object, &light, &point!(1.1, 0, 0), &eyev, &normalv, 1.0)
         );
     }
 }
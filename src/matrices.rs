@@ -10,6 +10,105 @@ impl<const N: usize> Matrix<N> {
     Self(input)
   }
 
+  pub const fn identity() -> Self {
+    let mut output = [[0.0; N]; N];
+    let mut i = 0;
+
+    while i < N {
+      output[i][i] = 1.0;
+      i += 1;
+    }
+
+    Self(output)
+  }
+
+  pub fn to_bytes(&self) -> Vec<u8> {
+    self
+      .0
+      .iter()
+      .flatten()
+      .flat_map(|value| value.to_le_bytes())
+      .collect()
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+    if bytes.len() != N * N * 8 {
+      return None;
+    }
+
+    let mut output = [[0.0f64; N]; N];
+
+    for (value, chunk) in output.iter_mut().flatten().zip(bytes.chunks_exact(8)) {
+      *value = f64::from_le_bytes(chunk.try_into().ok()?);
+    }
+
+    Some(Matrix::new(output))
+  }
+
+  fn largest_entry(&self) -> f64 {
+    self
+      .0
+      .iter()
+      .flatten()
+      .fold(0.0f64, |acc, value| acc.max(value.abs()))
+  }
+
+  // a pivot this small relative to the largest entry is rounding noise, so the matrix is singular
+  fn pivot_tolerance(&self) -> f64 {
+    self.largest_entry() * N as f64 * f64::EPSILON
+  }
+
+  // Doolittle decomposition with partial pivoting, then one forward/back substitution
+  // per column of the identity
+  pub fn lu_inverse(&self) -> Option<Self> {
+    let mut lu = self.0;
+    let mut permutation = [0; N];
+    for (i, index) in permutation.iter_mut().enumerate() {
+      *index = i;
+    }
+
+    let tolerance = self.pivot_tolerance();
+
+    for col in 0..N {
+      let pivot = (col..N).max_by(|a, b| lu[*a][col].abs().total_cmp(&lu[*b][col].abs()))?;
+
+      if lu[pivot][col].abs() <= tolerance {
+        return None;
+      }
+
+      lu.swap(col, pivot);
+      permutation.swap(col, pivot);
+
+      let pivot_row = lu[col];
+      for row in lu.iter_mut().skip(col + 1) {
+        let factor = row[col] / pivot_row[col];
+        row[col] = factor;
+
+        for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(col + 1) {
+          *value -= factor * pivot_value;
+        }
+      }
+    }
+
+    // solving against each column of the permuted identity gives the inverse's columns
+    let mut columns = [[0.0; N]; N];
+
+    for (col, column) in columns.iter_mut().enumerate() {
+      for row in 0..N {
+        let identity = if permutation[row] == col { 1.0 } else { 0.0 };
+        let sum = (0..row).fold(0.0, |acc, k| acc + lu[row][k] * column[k]);
+        column[row] = identity - sum;
+      }
+
+      for row in (0..N).rev() {
+        let sum = (row + 1..N).fold(0.0, |acc, k| acc + lu[row][k] * column[k]);
+        column[row] = (column[row] - sum) / lu[row][row];
+      }
+    }
+
+    Some(Matrix(columns).transpose())
+  }
+
   pub fn transpose(&self) -> Self {
     let mut output = [[0.0f64; N]; N];
 
@@ -75,8 +174,36 @@ impl<const N: usize> PartialEq for Matrix<N> {
   }
 }
 
+// a nested array of rows
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Matrix<N> {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(self.0.iter().map(|row| row.as_slice()))
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for Matrix<N> {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let rows = Vec::<Vec<f64>>::deserialize(deserializer)?;
+
+    if rows.len() != N || rows.iter().any(|row| row.len() != N) {
+      return Err(serde::de::Error::custom(format!(
+        "expected a {N}x{N} matrix"
+      )));
+    }
+
+    let mut output = [[0.0; N]; N];
+    for (out_row, row) in output.iter_mut().zip(rows) {
+      out_row.copy_from_slice(&row);
+    }
+
+    Ok(Self(output))
+  }
+}
+
 impl Matrix<2> {
-  fn determinant(&self) -> f64 {
+  pub fn determinant(&self) -> f64 {
     let Matrix([[a, b], [c, d]]) = self;
 
     a * d - b * c
@@ -84,15 +211,6 @@ impl Matrix<2> {
 }
 
 impl Matrix<4> {
-  pub const fn identity() -> Self {
-    Matrix::new([
-      [1.0, 0.0, 0.0, 0.0],
-      [0.0, 1.0, 0.0, 0.0],
-      [0.0, 0.0, 1.0, 0.0],
-      [0.0, 0.0, 0.0, 1.0],
-    ])
-  }
-
   pub fn rotate_x(self, radians: f64) -> Self {
     self * rotation(Axis::X, radians)
   }
@@ -168,35 +286,84 @@ macro_rules! define_methods {
         }
       }
 
-      fn determinant(&self) -> f64 {
+      pub fn determinant(&self) -> f64 {
         (0..$size).fold(0.0, |acc, col| acc + self.cofactor(0, col) * self.0[0][col])
       }
+    }
+  };
+}
 
-      pub fn inverse(&self) -> Option<Self> {
-        let determinant = self.determinant();
+define_methods!(4);
+define_methods!(3);
 
-        if determinant == 0.0 {
-          return None;
-        }
+impl Matrix<4> {
+  pub fn is_affine(&self) -> bool {
+    self.0[3] == [0.0, 0.0, 0.0, 1.0]
+  }
 
-        let mut output = [[0.0; $size]; $size];
+  fn affine_inverse(&self) -> Option<Self> {
+    let Matrix([[a, b, c, x], [d, e, f, y], [g, h, i, z], _]) = *self;
+    let Matrix([[a, b, c], [d, e, f], [g, h, i]]) =
+      Matrix::new([[a, b, c], [d, e, f], [g, h, i]]).cofactor_inverse()?;
 
-        for row in 0..$size {
-          for col in 0..$size {
-            let cofactor = self.cofactor(row, col);
+    Some(Matrix::new([
+      [a, b, c, -(a * x + b * y + c * z)],
+      [d, e, f, -(d * x + e * y + f * z)],
+      [g, h, i, -(g * x + h * y + i * z)],
+      [0.0, 0.0, 0.0, 1.0],
+    ]))
+  }
 
-            output[col][row] = cofactor / determinant;
-          }
-        }
+  pub fn inverse(&self) -> Option<Self> {
+    if self.is_affine() {
+      self.affine_inverse()
+    } else {
+      self.lu_inverse()
+    }
+  }
+}
 
-        Some(Matrix(output))
+impl Matrix<3> {
+  fn cofactors(&self) -> Self {
+    let mut output = [[0.0; 3]; 3];
+
+    for (row, cofactors) in output.iter_mut().enumerate() {
+      for (col, cofactor) in cofactors.iter_mut().enumerate() {
+        *cofactor = self.cofactor(row, col);
       }
     }
-  };
-}
 
-define_methods!(4);
-define_methods!(3);
+    Matrix(output)
+  }
+
+  fn cofactor_inverse(&self) -> Option<Self> {
+    // every cofactor is needed for the adjugate anyway, so compute them once
+    // and expand the determinant along the first row from the same values
+    let cofactors = self.cofactors();
+    let determinant = (0..3).fold(0.0, |acc, col| acc + cofactors[0][col] * self.0[0][col]);
+
+    // the determinant is the product of three pivots, so one of them falling under
+    // lu_inverse's tolerance shows up as a determinant under tolerance * scale^2
+    let scale = self.largest_entry();
+    if determinant.abs() <= self.pivot_tolerance() * scale * scale {
+      return None;
+    }
+
+    let mut output = [[0.0; 3]; 3];
+
+    for (row, cofactors) in cofactors.0.iter().enumerate() {
+      for (col, cofactor) in cofactors.iter().enumerate() {
+        output[col][row] = cofactor / determinant;
+      }
+    }
+
+    Some(Matrix(output))
+  }
+
+  pub fn inverse(&self) -> Option<Self> {
+    self.cofactor_inverse()
+  }
+}
 
 #[cfg(test)]
 mod test {
@@ -319,6 +486,19 @@ mod test {
     let tuple = Tuple::new(1.0, 2.0, 3.0, 4.0);
 
     assert_eq!(tuple, Matrix::new(identity) * tuple);
+    assert_eq!(Matrix::new(identity), Matrix::<4>::identity());
+
+    let matrix = Matrix::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.5]]);
+    assert_eq!(
+      Matrix::new([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
+      Matrix::<3>::identity()
+    );
+    assert_eq!(matrix, matrix * Matrix::identity());
+    assert_eq!(matrix, Matrix::identity() * matrix);
+
+    let matrix = Matrix::new([[-3.0, 5.0], [1.0, -2.0]]);
+    assert_eq!(matrix, matrix * Matrix::identity());
+    assert_eq!(matrix, Matrix::identity() * matrix);
   }
 
   #[test]
@@ -460,4 +640,145 @@ mod test {
     let c = a * b;
     assert_eq!(a, c * b.inverse().unwrap());
   }
+
+  #[test]
+  fn test_lu_inverse() {
+    let matrices = [
+      Matrix::new([
+        [8.0, -5.0, 9.0, 2.0],
+        [7.0, 5.0, 6.0, 1.0],
+        [-6.0, 0.0, 9.0, 6.0],
+        [-3.0, 0.0, -9.0, -4.0],
+      ]),
+      Matrix::new([
+        [9.0, 3.0, 0.0, 9.0],
+        [-5.0, -2.0, -6.0, -3.0],
+        [-4.0, 9.0, 6.0, 4.0],
+        [-7.0, 6.0, 6.0, 2.0],
+      ]),
+      // needs a row swap: the first pivot is zero
+      Matrix::new([
+        [0.0, 2.0, 1.0, 0.0],
+        [3.0, 0.0, 0.0, 1.0],
+        [0.0, 1.0, 4.0, 2.0],
+        [1.0, 0.0, 2.0, 5.0],
+      ]),
+    ];
+
+    for matrix in matrices {
+      assert_eq!(Matrix::identity(), matrix * matrix.lu_inverse().unwrap());
+      assert_eq!(Matrix::identity(), matrix.lu_inverse().unwrap() * matrix);
+    }
+
+    let matrix = Matrix::new([[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]]);
+    assert_eq!(matrix.cofactor_inverse(), matrix.lu_inverse());
+
+    let singular = Matrix::new([
+      [1.0, 2.0, 3.0, 4.0],
+      [2.0, 4.0, 6.0, 8.0],
+      [0.0, 1.0, 0.0, 1.0],
+      [5.0, 0.0, 1.0, 1.0],
+    ]);
+    assert_eq!(None, singular.lu_inverse());
+  }
+
+  #[test]
+  fn test_inverse_singular() {
+    let matrix = Matrix::new([
+      [-4.0, 2.0, -2.0, -3.0],
+      [9.0, 6.0, 2.0, 6.0],
+      [0.0, -5.0, 1.0, -5.0],
+      [0.0, 0.0, 0.0, 0.0],
+    ]);
+
+    assert_eq!(None, matrix.inverse());
+  }
+
+  #[test]
+  fn test_inverse_3() {
+    let matrix = Matrix::new([[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]]);
+
+    assert_eq!(
+      Matrix::new([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
+      matrix * matrix.inverse().unwrap()
+    );
+  }
+
+  #[test]
+  fn test_bytes() {
+    let matrix = Matrix::new([
+      [8.0, -5.0, 9.0, 2.0],
+      [7.0, 5.0, 6.0, 1.0],
+      [-6.0, 0.0, 9.0, 6.0],
+      [-3.0, 0.0, -9.0, -4.5],
+    ]);
+
+    let bytes = matrix.to_bytes();
+    assert_eq!(128, bytes.len());
+    assert_eq!(&8f64.to_le_bytes(), &bytes[0..8]);
+    assert_eq!(Some(matrix), Matrix::<4>::from_bytes(&bytes));
+
+    assert_eq!(None, Matrix::<4>::from_bytes(&bytes[..120]));
+    assert_eq!(None, Matrix::<4>::from_bytes(&[]));
+    assert_eq!(None, Matrix::<3>::from_bytes(&bytes));
+  }
+
+  #[test]
+  fn test_affine_inverse() {
+    use std::f64::consts::PI;
+
+    let transforms = [
+      Matrix::identity(),
+      translation(5.0, -3.0, 2.0),
+      scaling(2.0, 3.0, 4.0),
+      rotation(Axis::Y, PI / 3.0),
+      rotation(Axis::Z, PI / 5.0) * scaling(1.0, 0.5, 1.0),
+      translation(1.0, 2.0, 3.0) * rotation(Axis::Y, 1.0) * scaling(2.0, 2.0, 0.5),
+      shearing(1.0, 0.0, 0.5, 0.0, 0.0, 2.0) * translation(-4.0, 0.0, 1.0),
+    ];
+
+    for transform in transforms {
+      assert!(transform.is_affine());
+      assert_eq!(transform.lu_inverse(), transform.inverse());
+      assert_eq!(Matrix::identity(), transform * transform.inverse().unwrap());
+    }
+
+    assert_eq!(None, scaling(1.0, 0.0, 1.0).inverse());
+
+    // rank deficient, but rounding leaves a tiny nonzero determinant
+    let near_singular = [
+      rotation(Axis::X, 0.7)
+        * scaling(1.0, 0.0, 1.0)
+        * rotation(Axis::Y, 0.3)
+        * rotation(Axis::X, 1.1),
+      rotation(Axis::Z, 0.4) * scaling(2.0, 1e-17, 3.0) * rotation(Axis::Y, 0.9),
+      shearing(1.0, 1.0, 1.0, 1.0, 1.0, 1.0) * translation(1.0, 2.0, 3.0),
+    ];
+
+    for transform in near_singular {
+      assert!(transform.is_affine());
+      assert_eq!(None, transform.lu_inverse());
+      assert_eq!(None, transform.inverse());
+    }
+
+    let projective = Matrix::new([
+      [8.0, -5.0, 9.0, 2.0],
+      [7.0, 5.0, 6.0, 1.0],
+      [-6.0, 0.0, 9.0, 6.0],
+      [-3.0, 0.0, -9.0, -4.0],
+    ]);
+    assert!(!projective.is_affine());
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serde() {
+    let matrix = Matrix::new([[1.0, 2.0], [3.5, -4.0]]);
+    let json = serde_json::to_string(&matrix).unwrap();
+    assert_eq!("[[1.0,2.0],[3.5,-4.0]]", json);
+    assert_eq!(matrix, serde_json::from_str::<Matrix<2>>(&json).unwrap());
+
+    assert!(serde_json::from_str::<Matrix<2>>("[[1.0,2.0],[3.0]]").is_err());
+    assert!(serde_json::from_str::<Matrix<2>>("[[1.0,2.0]]").is_err());
+  }
 }
@@ -0,0 +1,426 @@
+use crate::{
+    groups::Group,
+    shapes::Shape,
+    triangles::{SmoothTriangle, Triangle},
+    tuples::Tuple,
+};
+
+#[derive(Debug)]
+pub struct ParsedObj {
+    pub vertices: Vec<Tuple>,
+    pub normals: Vec<Tuple>,
+    // lines that weren't understood
+    pub ignored: usize,
+    default_group: Group,
+    named_groups: Vec<(String, Group)>,
+    // index into named_groups that faces are currently added to
+    current_group: Option<usize>,
+}
+
+impl ParsedObj {
+    pub fn default_group(&self) -> &Group {
+        &self.default_group
+    }
+
+    pub fn named_group(&self, name: &str) -> Option<&Group> {
+        self.named_groups
+            .iter()
+            .find(|(group_name, _)| group_name == name)
+            .map(|(_, group)| group)
+    }
+
+    // for giving part of a model its own material with Shape::set_material
+    pub fn named_group_mut(&mut self, name: &str) -> Option<&mut Group> {
+        self.named_groups
+            .iter_mut()
+            .find(|(group_name, _)| group_name == name)
+            .map(|(_, group)| group)
+    }
+
+    pub fn to_group(self) -> Group {
+        let mut group = self.default_group;
+
+        for (_, named_group) in self.named_groups {
+            group.add_child(Box::new(named_group));
+        }
+
+        group
+    }
+
+    // a face corner is v, v/vt, v/vt/vn or v//vn; texture indices are skipped
+    fn corner(&self, arg: &str) -> Option<(Tuple, Option<Tuple>)> {
+        let mut indices = arg.split('/');
+        let vertex = lookup(&self.vertices, indices.next()?)?;
+
+        let normal = match indices.nth(1) {
+            Some(index) if !index.is_empty() => Some(lookup(&self.normals, index)?),
+            _ => None,
+        };
+
+        Some((vertex, normal))
+    }
+
+    // polygons are split into a fan of triangles around the first vertex
+    fn parse_face(&mut self, args: &[&str]) -> Option<()> {
+        let corners: Vec<_> = args
+            .iter()
+            .map(|arg| self.corner(arg))
+            .collect::<Option<_>>()?;
+
+        if corners.len() < 3 {
+            return None;
+        }
+
+        let group = match self.current_group {
+            Some(index) => &mut self.named_groups[index].1,
+            None => &mut self.default_group,
+        };
+
+        let (p1, n1) = corners[0];
+
+        for pair in corners[1..].windows(2) {
+            let [(p2, n2), (p3, n3)] = [pair[0], pair[1]];
+
+            let triangle: Box<dyn Shape> = match (n1, n2, n3) {
+                (Some(n1), Some(n2), Some(n3)) => {
+                    Box::new(SmoothTriangle::new(p1, p2, p3, n1, n2, n3))
+                }
+                _ => Box::new(Triangle::new(p1, p2, p3)),
+            };
+            group.add_child(triangle);
+        }
+
+        Some(())
+    }
+
+    fn parse_group(&mut self, args: &[&str]) -> Option<()> {
+        let [name] = args else {
+            return None;
+        };
+
+        let index = match self
+            .named_groups
+            .iter()
+            .position(|(group_name, _)| group_name == name)
+        {
+            Some(index) => index,
+            None => {
+                self.named_groups.push((name.to_string(), Group::new()));
+                self.named_groups.len() - 1
+            }
+        };
+        self.current_group = Some(index);
+
+        Some(())
+    }
+}
+
+// indices are 1-based, or relative to the end when negative
+fn lookup(items: &[Tuple], index: &str) -> Option<Tuple> {
+    let index: isize = index.parse().ok()?;
+
+    let position = match index {
+        0 => return None,
+        1.. => index - 1,
+        _ => items.len() as isize + index,
+    };
+
+    items.get(usize::try_from(position).ok()?).copied()
+}
+
+fn parse_xyz(args: &[&str]) -> Option<(f64, f64, f64)> {
+    let [x, y, z] = args else {
+        return None;
+    };
+
+    Some((x.parse().ok()?, y.parse().ok()?, z.parse().ok()?))
+}
+
+pub fn parse_obj(input: &str) -> ParsedObj {
+    let mut parsed = ParsedObj {
+        vertices: Vec::new(),
+        normals: Vec::new(),
+        ignored: 0,
+        default_group: Group::new(),
+        named_groups: Vec::new(),
+        current_group: None,
+    };
+
+    for line in input.lines() {
+        let mut tokens = line.split_whitespace();
+        let command = tokens.next();
+        let args: Vec<_> = tokens.collect();
+
+        let result = match command {
+            Some("v") => parse_xyz(&args).map(|(x, y, z)| parsed.vertices.push(point!(x, y, z))),
+            Some("vn") => parse_xyz(&args).map(|(x, y, z)| parsed.normals.push(vector!(x, y, z))),
+            Some("f") => parsed.parse_face(&args),
+            Some("g") => parsed.parse_group(&args),
+            _ => None,
+        };
+
+        if result.is_none() {
+            parsed.ignored += 1;
+        }
+    }
+
+    parsed
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{materials::Material, rays::Ray};
+    use std::any::Any;
+
+    fn triangles(group: &Group) -> Vec<&Triangle> {
+        group
+            .children()
+            .iter()
+            .map(|child| {
+                (child.as_ref() as &dyn Any)
+                    .downcast_ref::<Triangle>()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ignores_gibberish() {
+        let input = "There was a young lady named Bright
+who traveled much faster than light.
+She set out one day
+in a relative way,
+and came back the previous night.";
+
+        let parsed = parse_obj(input);
+
+        assert_eq!(5, parsed.ignored);
+        assert!(parsed.vertices.is_empty());
+    }
+
+    #[test]
+    fn test_vertices() {
+        let input = "v -1 1 0
+v -1.0000 0.5000 0.0000
+v 1 0 0
+v 1 1 0";
+
+        let parsed = parse_obj(input);
+
+        assert_eq!(
+            vec![
+                point!(-1, 1, 0),
+                point!(-1, 0.5, 0),
+                point!(1, 0, 0),
+                point!(1, 1, 0)
+            ],
+            parsed.vertices
+        );
+    }
+
+    #[test]
+    fn test_triangle_faces() {
+        let input = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+f 1 2 3
+f 1 3 4";
+
+        let parsed = parse_obj(input);
+        let v = &parsed.vertices;
+        let faces = triangles(parsed.default_group());
+
+        assert_eq!(2, faces.len());
+        assert_eq!(
+            (v[0], v[1], v[2]),
+            (faces[0].p1(), faces[0].p2(), faces[0].p3())
+        );
+        assert_eq!(
+            (v[0], v[2], v[3]),
+            (faces[1].p1(), faces[1].p2(), faces[1].p3())
+        );
+    }
+
+    #[test]
+    fn test_polygon_fan() {
+        let input = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+v 0 2 0
+
+f 1 2 3 4 5";
+
+        let parsed = parse_obj(input);
+        let v = &parsed.vertices;
+        let faces = triangles(parsed.default_group());
+
+        assert_eq!(3, faces.len());
+        assert_eq!(
+            (v[0], v[1], v[2]),
+            (faces[0].p1(), faces[0].p2(), faces[0].p3())
+        );
+        assert_eq!(
+            (v[0], v[2], v[3]),
+            (faces[1].p1(), faces[1].p2(), faces[1].p3())
+        );
+        assert_eq!(
+            (v[0], v[3], v[4]),
+            (faces[2].p1(), faces[2].p2(), faces[2].p3())
+        );
+    }
+
+    #[test]
+    fn test_named_groups() {
+        let input = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4";
+
+        let parsed = parse_obj(input);
+        let v = &parsed.vertices;
+
+        assert!(parsed.default_group().children().is_empty());
+        assert!(parsed.named_group("ThirdGroup").is_none());
+
+        let first = triangles(parsed.named_group("FirstGroup").unwrap());
+        assert_eq!(1, first.len());
+        assert_eq!(
+            (v[0], v[1], v[2]),
+            (first[0].p1(), first[0].p2(), first[0].p3())
+        );
+
+        let second = triangles(parsed.named_group("SecondGroup").unwrap());
+        assert_eq!(1, second.len());
+        assert_eq!(
+            (v[0], v[2], v[3]),
+            (second[0].p1(), second[0].p2(), second[0].p3())
+        );
+
+        let group = parsed.to_group();
+        assert_eq!(2, group.children().len());
+
+        let ray = Ray::new(point!(0.5, 0.5, -5), vector!(0, 0, 1));
+        assert_eq!(1, group.intersect(&ray).count());
+    }
+
+    #[test]
+    fn test_named_group_material() {
+        let input = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4";
+
+        let mut parsed = parse_obj(input);
+        assert!(parsed.named_group_mut("ThirdGroup").is_none());
+
+        let mut red = Material::new();
+        red.color = color!(1, 0, 0);
+        parsed
+            .named_group_mut("FirstGroup")
+            .unwrap()
+            .set_material(red.clone());
+
+        let group = parsed.to_group();
+        let color_at = |x, y| {
+            let ray = Ray::new(point!(x, y, -5), vector!(0, 0, 1));
+            let xs = group.intersect(&ray);
+            xs.hit().unwrap().object.material().color
+        };
+
+        assert_eq!(red.color, color_at(-0.5, 0.25));
+        assert_eq!(Material::new().color, color_at(0.5, 0.75));
+    }
+
+    #[test]
+    fn test_relative_indices() {
+        let input = "v 0 0 0
+v 1 0 0
+v 0 1 0
+f -3 -2 -1
+f 1 2 9
+f 0 1 2";
+
+        let parsed = parse_obj(input);
+        let faces = triangles(parsed.default_group());
+
+        assert_eq!(2, parsed.ignored);
+        assert_eq!(1, faces.len());
+        assert_eq!(point!(0, 1, 0), faces[0].p3());
+
+        let group = parsed.to_group();
+        assert_eq!(1, group.children().len());
+        assert_eq!(
+            vector!(0, 0, -1),
+            group.children()[0].normal_at(&point!(0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_normals() {
+        let input = "vn 0 0 1
+vn 0.707 0 -0.707
+vn 1 2 3";
+
+        let parsed = parse_obj(input);
+
+        assert_eq!(
+            vec![
+                vector!(0, 0, 1),
+                vector!(0.707, 0, -0.707),
+                vector!(1, 2, 3)
+            ],
+            parsed.normals
+        );
+    }
+
+    #[test]
+    fn test_faces_with_normals() {
+        let input = "v 0 1 0
+v -1 0 0
+v 1 0 0
+
+vn -1 0 0
+vn 1 0 0
+vn 0 1 0
+
+f 1//3 2//1 3//2
+f 1/0/3 2/102/1 3/14/2";
+
+        let parsed = parse_obj(input);
+        let (v, n) = (&parsed.vertices, &parsed.normals);
+        let faces: Vec<_> = parsed
+            .default_group()
+            .children()
+            .iter()
+            .map(|child| {
+                (child.as_ref() as &dyn Any)
+                    .downcast_ref::<SmoothTriangle>()
+                    .unwrap()
+            })
+            .collect();
+
+        assert_eq!(2, faces.len());
+        for face in faces {
+            let triangle = face.triangle();
+            assert_eq!(
+                (v[0], v[1], v[2]),
+                (triangle.p1(), triangle.p2(), triangle.p3())
+            );
+            assert_eq!((n[2], n[0], n[1]), (face.n1, face.n2, face.n3));
+        }
+    }
+}
@@ -0,0 +1,587 @@
+use crate::{canvas::Canvas, matrices::Matrix, shapes::Shape, tuples::Tuple};
+use std::{f64::consts::PI, sync::Arc};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PatternKind {
+    Stripe(StripePattern),
+    Gradient(GradientPattern),
+    Ring(RingPattern),
+    Checker(CheckerPattern),
+    Map(TextureMap),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    transform: Matrix<4>,
+    // cached for every lookup; set_pattern_transform only accepts invertible transforms
+    inverse: Matrix<4>,
+}
+
+// the cached inverse is rebuilt from transform when loading
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PatternData {
+    kind: PatternKind,
+    transform: Matrix<4>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Pattern {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PatternData {
+            kind: self.kind.clone(),
+            transform: self.transform,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Pattern {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = PatternData::deserialize(deserializer)?;
+        let inverse = data
+            .transform
+            .inverse()
+            .ok_or_else(|| serde::de::Error::custom("pattern transform is singular"))?;
+
+        Ok(Pattern {
+            kind: data.kind,
+            transform: data.transform,
+            inverse,
+        })
+    }
+}
+
+impl Pattern {
+    pub const fn new(kind: PatternKind) -> Self {
+        Self {
+            kind,
+            transform: Matrix::<4>::identity(),
+            inverse: Matrix::<4>::identity(),
+        }
+    }
+
+    pub fn transform(&self) -> &Matrix<4> {
+        &self.transform
+    }
+
+    // rejected here rather than panicking partway through shading
+    pub fn set_pattern_transform(&mut self, transform: Matrix<4>) {
+        self.inverse = transform.inverse().expect("pattern transform is singular");
+        self.transform = transform;
+    }
+
+    pub fn pattern_at(&self, point: &Tuple) -> Tuple {
+        match &self.kind {
+            PatternKind::Stripe(pattern) => pattern.pattern_at(point),
+            PatternKind::Gradient(pattern) => pattern.pattern_at(point),
+            PatternKind::Ring(pattern) => pattern.pattern_at(point),
+            PatternKind::Checker(pattern) => pattern.pattern_at(point),
+            PatternKind::Map(map) => map.pattern_at(point),
+        }
+    }
+
+    pub fn pattern_at_shape(&self, shape: &dyn Shape, world_point: &Tuple) -> Tuple {
+        let object_point = shape.world_to_object(world_point);
+        let pattern_point = self.inverse * object_point;
+
+        self.pattern_at(&pattern_point)
+    }
+}
+
+fn is_even(value: f64) -> bool {
+    value.floor().rem_euclid(2.0) == 0.0
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StripePattern {
+    pub a: Tuple,
+    pub b: Tuple,
+}
+
+impl StripePattern {
+    pub const fn new(a: Tuple, b: Tuple) -> Self {
+        Self { a, b }
+    }
+
+    pub fn stripe_at(&self, point: &Tuple) -> Tuple {
+        if is_even(point.0) {
+            self.a
+        } else {
+            self.b
+        }
+    }
+
+    pub fn pattern_at(&self, point: &Tuple) -> Tuple {
+        self.stripe_at(point)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradientPattern {
+    pub a: Tuple,
+    pub b: Tuple,
+}
+
+impl GradientPattern {
+    pub const fn new(a: Tuple, b: Tuple) -> Self {
+        Self { a, b }
+    }
+
+    pub fn pattern_at(&self, point: &Tuple) -> Tuple {
+        self.a + (self.b - self.a) * (point.0 - point.0.floor())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RingPattern {
+    pub a: Tuple,
+    pub b: Tuple,
+}
+
+impl RingPattern {
+    pub const fn new(a: Tuple, b: Tuple) -> Self {
+        Self { a, b }
+    }
+
+    pub fn pattern_at(&self, point: &Tuple) -> Tuple {
+        if is_even((point.0.powi(2) + point.2.powi(2)).sqrt()) {
+            self.a
+        } else {
+            self.b
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheckerPattern {
+    pub a: Tuple,
+    pub b: Tuple,
+}
+
+impl CheckerPattern {
+    pub const fn new(a: Tuple, b: Tuple) -> Self {
+        Self { a, b }
+    }
+
+    pub fn pattern_at(&self, point: &Tuple) -> Tuple {
+        if is_even(point.0.floor() + point.1.floor() + point.2.floor()) {
+            self.a
+        } else {
+            self.b
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TexturePattern {
+    // shared so cloning a textured material doesn't copy the image
+    pub canvas: Arc<Canvas>,
+}
+
+impl TexturePattern {
+    pub fn new(canvas: Canvas) -> Self {
+        Self {
+            canvas: Arc::new(canvas),
+        }
+    }
+
+    // v runs bottom to top while canvas rows run top to bottom
+    pub fn uv_pattern_at(&self, u: f64, v: f64) -> Tuple {
+        let u = u.clamp(0.0, 1.0);
+        let v = 1.0 - v.clamp(0.0, 1.0);
+
+        let x = u * self.canvas.width.saturating_sub(1) as f64;
+        let y = v * self.canvas.height.saturating_sub(1) as f64;
+
+        self.canvas.pixel_at(x.round() as usize, y.round() as usize)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UvCheckers {
+    pub width: f64,
+    pub height: f64,
+    pub a: Tuple,
+    pub b: Tuple,
+}
+
+impl UvCheckers {
+    pub const fn new(width: f64, height: f64, a: Tuple, b: Tuple) -> Self {
+        Self {
+            width,
+            height,
+            a,
+            b,
+        }
+    }
+
+    pub fn uv_pattern_at(&self, u: f64, v: f64) -> Tuple {
+        if is_even((u * self.width).floor() + (v * self.height).floor()) {
+            self.a
+        } else {
+            self.b
+        }
+    }
+}
+
+// u wraps around the y axis and v runs from the south to the north pole
+pub fn spherical_map(point: &Tuple) -> (f64, f64) {
+    let theta = point.0.atan2(point.2);
+    let radius = vector!(point.0, point.1, point.2).magnitude();
+    let phi = (point.1 / radius).acos();
+
+    let raw_u = theta / (2.0 * PI);
+
+    (1.0 - (raw_u + 0.5), 1.0 - phi / PI)
+}
+
+pub fn planar_map(point: &Tuple) -> (f64, f64) {
+    (point.0.rem_euclid(1.0), point.2.rem_euclid(1.0))
+}
+
+pub fn cylindrical_map(point: &Tuple) -> (f64, f64) {
+    let theta = point.0.atan2(point.2);
+    let raw_u = theta / (2.0 * PI);
+
+    (1.0 - (raw_u + 0.5), point.1.rem_euclid(1.0))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UvMapping {
+    Spherical,
+    Planar,
+    Cylindrical,
+}
+
+impl UvMapping {
+    pub fn map(&self, point: &Tuple) -> (f64, f64) {
+        match self {
+            UvMapping::Spherical => spherical_map(point),
+            UvMapping::Planar => planar_map(point),
+            UvMapping::Cylindrical => cylindrical_map(point),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UvPattern {
+    Texture(TexturePattern),
+    Checkers(UvCheckers),
+}
+
+impl UvPattern {
+    pub fn uv_pattern_at(&self, u: f64, v: f64) -> Tuple {
+        match self {
+            UvPattern::Texture(pattern) => pattern.uv_pattern_at(u, v),
+            UvPattern::Checkers(pattern) => pattern.uv_pattern_at(u, v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextureMap {
+    pub pattern: UvPattern,
+    pub mapping: UvMapping,
+}
+
+impl TextureMap {
+    pub const fn new(pattern: UvPattern, mapping: UvMapping) -> Self {
+        Self { pattern, mapping }
+    }
+
+    pub fn pattern_at(&self, point: &Tuple) -> Tuple {
+        let (u, v) = self.mapping.map(point);
+
+        self.pattern.uv_pattern_at(u, v)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{canvas::Canvas, constants::EPSILON, spheres::Sphere, transformations::*};
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    const BLACK: Tuple = color!(0, 0, 0);
+    const WHITE: Tuple = color!(1, 1, 1);
+
+    #[test]
+    fn test_constructor() {
+        let pattern = StripePattern::new(WHITE, BLACK);
+
+        assert_eq!(WHITE, pattern.a);
+        assert_eq!(BLACK, pattern.b);
+    }
+
+    #[test]
+    fn test_stripe_at() {
+        let pattern = StripePattern::new(WHITE, BLACK);
+
+        assert_eq!(WHITE, pattern.stripe_at(&point!(0, 0, 0)));
+        assert_eq!(WHITE, pattern.stripe_at(&point!(0, 1, 0)));
+        assert_eq!(WHITE, pattern.stripe_at(&point!(0, 2, 0)));
+
+        assert_eq!(WHITE, pattern.stripe_at(&point!(0, 0, 1)));
+        assert_eq!(WHITE, pattern.stripe_at(&point!(0, 0, 2)));
+
+        assert_eq!(WHITE, pattern.stripe_at(&point!(0.9, 0, 0)));
+        assert_eq!(BLACK, pattern.stripe_at(&point!(1, 0, 0)));
+        assert_eq!(BLACK, pattern.stripe_at(&point!(-0.1, 0, 0)));
+        assert_eq!(BLACK, pattern.stripe_at(&point!(-1, 0, 0)));
+        assert_eq!(WHITE, pattern.stripe_at(&point!(-1.1, 0, 0)));
+    }
+
+    #[test]
+    fn test_gradient() {
+        let pattern = GradientPattern::new(WHITE, BLACK);
+
+        assert_eq!(WHITE, pattern.pattern_at(&point!(0, 0, 0)));
+        assert_eq!(
+            color!(0.75, 0.75, 0.75),
+            pattern.pattern_at(&point!(0.25, 0, 0))
+        );
+        assert_eq!(
+            color!(0.5, 0.5, 0.5),
+            pattern.pattern_at(&point!(0.5, 0, 0))
+        );
+        assert_eq!(
+            color!(0.25, 0.25, 0.25),
+            pattern.pattern_at(&point!(0.75, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_ring() {
+        let pattern = RingPattern::new(WHITE, BLACK);
+
+        assert_eq!(WHITE, pattern.pattern_at(&point!(0, 0, 0)));
+        assert_eq!(BLACK, pattern.pattern_at(&point!(1, 0, 0)));
+        assert_eq!(BLACK, pattern.pattern_at(&point!(0, 0, 1)));
+        assert_eq!(BLACK, pattern.pattern_at(&point!(0.708, 0, 0.708)));
+    }
+
+    #[test]
+    fn test_checker() {
+        let pattern = CheckerPattern::new(WHITE, BLACK);
+
+        assert_eq!(WHITE, pattern.pattern_at(&point!(0, 0, 0)));
+        assert_eq!(WHITE, pattern.pattern_at(&point!(0.99, 0, 0)));
+        assert_eq!(BLACK, pattern.pattern_at(&point!(1.01, 0, 0)));
+
+        assert_eq!(WHITE, pattern.pattern_at(&point!(0, 0.99, 0)));
+        assert_eq!(BLACK, pattern.pattern_at(&point!(0, 1.01, 0)));
+
+        assert_eq!(WHITE, pattern.pattern_at(&point!(0, 0, 0.99)));
+        assert_eq!(BLACK, pattern.pattern_at(&point!(0, 0, 1.01)));
+
+        assert_eq!(WHITE, pattern.pattern_at(&point!(2.5, 0, 0)));
+        assert_eq!(BLACK, pattern.pattern_at(&point!(-0.5, 0, 0)));
+        assert_eq!(WHITE, pattern.pattern_at(&point!(1.5, 1.5, 0)));
+    }
+
+    #[test]
+    fn test_pattern_at() {
+        let pattern = Pattern::new(PatternKind::Checker(CheckerPattern::new(WHITE, BLACK)));
+        assert_eq!(Matrix::<4>::identity(), *pattern.transform());
+        assert_eq!(BLACK, pattern.pattern_at(&point!(1.5, 0, 0)));
+
+        let pattern = Pattern::new(PatternKind::Stripe(StripePattern::new(WHITE, BLACK)));
+        assert_eq!(WHITE, pattern.pattern_at(&point!(0, 5, 5)));
+    }
+
+    #[test]
+    fn test_pattern_at_shape() {
+        let stripes = Pattern::new(PatternKind::Stripe(StripePattern::new(WHITE, BLACK)));
+
+        let mut sphere = Sphere::new();
+        sphere.set_transform(scaling(2.0, 2.0, 2.0));
+        assert_eq!(WHITE, stripes.pattern_at_shape(&sphere, &point!(1.5, 0, 0)));
+
+        let sphere = Sphere::new();
+        let mut pattern = stripes.clone();
+        pattern.set_pattern_transform(scaling(2.0, 2.0, 2.0));
+        assert_eq!(WHITE, pattern.pattern_at_shape(&sphere, &point!(1.5, 0, 0)));
+
+        let mut sphere = Sphere::new();
+        sphere.set_transform(scaling(2.0, 2.0, 2.0));
+        let mut pattern = stripes.clone();
+        pattern.set_pattern_transform(translation(0.5, 0.0, 0.0));
+        assert_eq!(WHITE, pattern.pattern_at_shape(&sphere, &point!(2.5, 0, 0)));
+        assert_eq!(BLACK, pattern.pattern_at_shape(&sphere, &point!(3.5, 0, 0)));
+    }
+
+    #[test]
+    fn test_texture_uv_pattern_at() {
+        let mut canvas = Canvas::new(3, 3);
+        canvas.write_pixel(0, 0, &color!(1, 0, 0));
+        canvas.write_pixel(2, 0, &color!(0, 1, 0));
+        canvas.write_pixel(0, 2, &color!(0, 0, 1));
+        canvas.write_pixel(2, 2, &color!(1, 1, 0));
+        canvas.write_pixel(1, 1, &WHITE);
+
+        let pattern = TexturePattern::new(canvas);
+
+        assert_eq!(color!(0, 0, 1), pattern.uv_pattern_at(0.0, 0.0));
+        assert_eq!(color!(1, 1, 0), pattern.uv_pattern_at(1.0, 0.0));
+        assert_eq!(color!(1, 0, 0), pattern.uv_pattern_at(0.0, 1.0));
+        assert_eq!(color!(0, 1, 0), pattern.uv_pattern_at(1.0, 1.0));
+        assert_eq!(WHITE, pattern.uv_pattern_at(0.5, 0.5));
+        assert_eq!(color!(0, 1, 0), pattern.uv_pattern_at(1.2, 1.5));
+
+        // clones share the image rather than copying it
+        let clone = pattern.clone();
+        assert!(Arc::ptr_eq(&pattern.canvas, &clone.canvas));
+    }
+
+    #[test]
+    fn test_uv_checkers() {
+        let checkers = UvCheckers::new(2.0, 2.0, BLACK, WHITE);
+
+        assert_eq!(BLACK, checkers.uv_pattern_at(0.0, 0.0));
+        assert_eq!(WHITE, checkers.uv_pattern_at(0.5, 0.0));
+        assert_eq!(WHITE, checkers.uv_pattern_at(0.0, 0.5));
+        assert_eq!(BLACK, checkers.uv_pattern_at(0.5, 0.5));
+        assert_eq!(BLACK, checkers.uv_pattern_at(1.0, 1.0));
+
+        let map = TextureMap::new(
+            UvPattern::Checkers(UvCheckers::new(16.0, 8.0, BLACK, WHITE)),
+            UvMapping::Spherical,
+        );
+
+        let cases = [
+            (point!(0.4315, 0.4670, 0.7719), WHITE),
+            (point!(-0.9654, 0.2552, -0.0534), BLACK),
+            (point!(0.1039, 0.7090, 0.6975), WHITE),
+            (point!(-0.4986, -0.7856, -0.3663), BLACK),
+            (point!(-0.0317, -0.9395, 0.3411), BLACK),
+            (point!(0.4809, -0.7721, 0.4154), BLACK),
+            (point!(0.0285, -0.9612, -0.2745), BLACK),
+            (point!(-0.5734, -0.2162, -0.7903), WHITE),
+            (point!(0.7688, -0.1470, 0.6223), BLACK),
+            (point!(-0.7652, 0.2175, 0.6060), BLACK),
+        ];
+
+        for (point, color) in cases {
+            assert_eq!(color, map.pattern_at(&point));
+        }
+    }
+
+    #[test]
+    fn test_spherical_map() {
+        let cases = [
+            (point!(0, 0, -1), (0.0, 0.5)),
+            (point!(1, 0, 0), (0.25, 0.5)),
+            (point!(0, 0, 1), (0.5, 0.5)),
+            (point!(-1, 0, 0), (0.75, 0.5)),
+            (point!(0, 1, 0), (0.5, 1.0)),
+            (point!(0, -1, 0), (0.5, 0.0)),
+            (point!(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0), (0.25, 0.75)),
+        ];
+
+        for (point, (u, v)) in cases {
+            let (mapped_u, mapped_v) = spherical_map(&point);
+            assert!((u - mapped_u).abs() < EPSILON, "{point:?}");
+            assert!((v - mapped_v).abs() < EPSILON, "{point:?}");
+        }
+    }
+
+    #[test]
+    fn test_planar_map() {
+        let cases = [
+            (point!(0.25, 0, 0.5), (0.25, 0.5)),
+            (point!(0.25, 0, -0.25), (0.25, 0.75)),
+            (point!(0.25, 0.5, -0.25), (0.25, 0.75)),
+            (point!(1.25, 0, 0.5), (0.25, 0.5)),
+            (point!(0.25, 0, -1.75), (0.25, 0.25)),
+            (point!(1, 0, -1), (0.0, 0.0)),
+            (point!(0, 0, 0), (0.0, 0.0)),
+            (point!(-0.25, 0, 0), (0.75, 0.0)),
+            (point!(-3.5, 0, 0), (0.5, 0.0)),
+        ];
+
+        for (point, (u, v)) in cases {
+            let (mapped_u, mapped_v) = planar_map(&point);
+            assert!((u - mapped_u).abs() < EPSILON, "{point:?}");
+            assert!((v - mapped_v).abs() < EPSILON, "{point:?}");
+        }
+    }
+
+    #[test]
+    fn test_cylindrical_map() {
+        let cases = [
+            (point!(0, 0, -1), (0.0, 0.0)),
+            (point!(0, 0.5, -1), (0.0, 0.5)),
+            (point!(0, 1, -1), (0.0, 0.0)),
+            (point!(FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2), (0.125, 0.5)),
+            (point!(1, 0.5, 0), (0.25, 0.5)),
+            (point!(FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), (0.375, 0.5)),
+            (point!(0, -0.25, 1), (0.5, 0.75)),
+            (point!(-FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), (0.625, 0.5)),
+            (point!(-1, 1.25, 0), (0.75, 0.25)),
+            (point!(-FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2), (0.875, 0.5)),
+        ];
+
+        for (point, (u, v)) in cases {
+            let (mapped_u, mapped_v) = cylindrical_map(&point);
+            assert!((u - mapped_u).abs() < EPSILON, "{point:?}");
+            assert!((v - mapped_v).abs() < EPSILON, "{point:?}");
+        }
+    }
+
+    #[test]
+    fn test_texture_map_on_sphere() {
+        let mut canvas = Canvas::new(3, 3);
+        canvas.write_pixel(0, 1, &color!(1, 0, 0));
+        canvas.write_pixel(1, 0, &color!(0, 1, 0));
+        canvas.write_pixel(1, 2, &color!(0, 0, 1));
+
+        let map = TextureMap::new(
+            UvPattern::Texture(TexturePattern::new(canvas)),
+            UvMapping::Spherical,
+        );
+        let pattern = Pattern::new(PatternKind::Map(map));
+
+        let mut sphere = Sphere::new();
+        sphere.set_transform(scaling(2.0, 2.0, 2.0));
+
+        assert_eq!(
+            color!(1, 0, 0),
+            pattern.pattern_at_shape(&sphere, &point!(0, 0, -2))
+        );
+        assert_eq!(
+            color!(0, 1, 0),
+            pattern.pattern_at_shape(&sphere, &point!(0, 2, 0))
+        );
+        assert_eq!(
+            color!(0, 0, 1),
+            pattern.pattern_at_shape(&sphere, &point!(0, -2, 0))
+        );
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut pattern = Pattern::new(PatternKind::Stripe(StripePattern::new(WHITE, BLACK)));
+        pattern.set_pattern_transform(translation(0.5, 0.0, 0.0));
+
+        let json = serde_json::to_string(&pattern).unwrap();
+        let loaded: Pattern = serde_json::from_str(&json).unwrap();
+        assert_eq!(pattern, loaded);
+
+        let mut singular: serde_json::Value = serde_json::from_str(&json).unwrap();
+        singular["transform"][0][0] = serde_json::json!(0.0);
+        assert!(serde_json::from_value::<Pattern>(singular).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{
+    bounds::AABB,
+    constants::EPSILON,
+    intersections::Intersections,
+    materials::Material,
+    matrices::Matrix,
+    rays::Ray,
+    shapes::{Shape, Transform},
+    tuples::Tuple,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plane {
+    transform: Transform,
+    pub material: Material,
+}
+
+impl Plane {
+    pub const fn new() -> Self {
+        Self {
+            transform: Transform::identity(),
+            material: Material::new(),
+        }
+    }
+}
+
+impl Default for Plane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for Plane {
+    fn transform(&self) -> &Matrix<4> {
+        self.transform.matrix()
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = Transform::new(transform);
+    }
+
+    fn inverse(&self) -> Option<Matrix<4>> {
+        self.transform.inverse()
+    }
+
+    fn inverse_transpose(&self) -> Option<Matrix<4>> {
+        self.transform.inverse_transpose()
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        if ray.direction.1.abs() < EPSILON {
+            return Intersections::empty();
+        }
+
+        Intersections::from(&[-ray.origin.1 / ray.direction.1], self)
+    }
+
+    fn local_normal_at(&self, _point: &Tuple) -> Tuple {
+        vector!(0, 1, 0)
+    }
+
+    fn bounds(&self) -> AABB {
+        AABB::new(
+            point!(f64::NEG_INFINITY, 0, f64::NEG_INFINITY),
+            point!(f64::INFINITY, 0, f64::INFINITY),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normal_at() {
+        let plane = Plane::new();
+
+        assert_eq!(vector!(0, 1, 0), plane.local_normal_at(&point!(0, 0, 0)));
+        assert_eq!(vector!(0, 1, 0), plane.local_normal_at(&point!(10, 0, -10)));
+        assert_eq!(vector!(0, 1, 0), plane.local_normal_at(&point!(-5, 0, 150)));
+    }
+
+    #[test]
+    fn test_intersect() {
+        let plane = Plane::new();
+
+        let ray = Ray::new(point!(0, 10, 0), vector!(0, 0, 1));
+        assert_eq!(0, plane.local_intersect(&ray).count());
+
+        let ray = Ray::new(point!(0, 0, 0), vector!(0, 0, 1));
+        assert_eq!(0, plane.local_intersect(&ray).count());
+
+        let ray = Ray::new(point!(0, 1, 0), vector!(0, -1, 0));
+        assert_eq!(
+            Intersections::from(&[1.0], &plane),
+            plane.local_intersect(&ray)
+        );
+
+        let ray = Ray::new(point!(0, -1, 0), vector!(0, 1, 0));
+        assert_eq!(
+            Intersections::from(&[1.0], &plane),
+            plane.local_intersect(&ray)
+        );
+    }
+}
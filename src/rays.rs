@@ -1,6 +1,6 @@
 use crate::{matrices::*, tuples::*};
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
 pub struct Ray {
     pub origin: Tuple,
     pub direction: Tuple,
@@ -15,14 +15,14 @@ impl Ray {
         self.origin + self.direction * time
     }
 
-    fn translate(&self, x: f64, y: f64, z: f64) -> Self {
+    pub fn translate(&self, x: f64, y: f64, z: f64) -> Self {
         Self {
             origin: self.origin.translate(x, y, z),
             direction: self.direction,
         }
     }
 
-    fn scale(&self, x: f64, y: f64, z: f64) -> Self {
+    pub fn scale(&self, x: f64, y: f64, z: f64) -> Self {
         Self {
             origin: self.origin.scale(x, y, z),
             direction: self.direction.scale(x, y, z),
@@ -0,0 +1,466 @@
+use crate::{
+    camera::Camera,
+    cones::Cone,
+    cubes::Cube,
+    cylinders::Cylinder,
+    groups::Group,
+    lights::{Light, PointLight},
+    materials::Material,
+    matrices::Matrix,
+    planes::Plane,
+    shapes::Shape,
+    spheres::Sphere,
+    transformations::{rotation, scaling, shearing, translation, view_transform, Axis},
+    world::{SceneWarning, World},
+};
+use std::{collections::HashMap, error::Error, fmt};
+use yaml_rust2::{ScanError, Yaml, YamlLoader};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Yaml(ScanError),
+    // the key or item that couldn't be understood
+    Invalid(String),
+    MissingCamera,
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Yaml(err) => write!(f, "invalid yaml: {err}"),
+            SceneError::Invalid(what) => write!(f, "invalid scene entry: {what}"),
+            SceneError::MissingCamera => write!(f, "the scene has no camera"),
+        }
+    }
+}
+
+impl Error for SceneError {}
+
+impl From<ScanError> for SceneError {
+    fn from(err: ScanError) -> Self {
+        SceneError::Yaml(err)
+    }
+}
+
+fn invalid(what: impl fmt::Debug) -> SceneError {
+    SceneError::Invalid(format!("{what:?}"))
+}
+
+fn number(value: &Yaml) -> Result<f64, SceneError> {
+    match value {
+        Yaml::Real(_) => value.as_f64().ok_or_else(|| invalid(value)),
+        Yaml::Integer(int) => Ok(*int as f64),
+        _ => Err(invalid(value)),
+    }
+}
+
+fn numbers<const N: usize>(values: &[Yaml]) -> Result<[f64; N], SceneError> {
+    if values.len() != N {
+        return Err(invalid(values));
+    }
+
+    let mut output = [0.0; N];
+    for (slot, value) in output.iter_mut().zip(values) {
+        *slot = number(value)?;
+    }
+
+    Ok(output)
+}
+
+fn xyz(value: &Yaml) -> Result<(f64, f64, f64), SceneError> {
+    let [x, y, z] = numbers(value.as_vec().ok_or_else(|| invalid(value))?)?;
+    Ok((x, y, z))
+}
+
+fn size(value: &Yaml) -> Result<usize, SceneError> {
+    value
+        .as_i64()
+        .and_then(|size| usize::try_from(size).ok())
+        .filter(|&size| size > 0)
+        .ok_or_else(|| invalid(value))
+}
+
+struct SceneBuilder {
+    // define: entries by name, with extend and references to earlier defines
+    // already resolved, so looking one up never recurses into another
+    defines: HashMap<String, Yaml>,
+    camera: Option<Camera>,
+    world: World,
+}
+
+impl SceneBuilder {
+    fn define(&mut self, item: &Yaml) -> Result<(), SceneError> {
+        let name = item["define"].as_str().ok_or_else(|| invalid(item))?;
+        let mut value = self.resolve(&item["value"])?;
+
+        if let Some(parent) = item["extend"].as_str() {
+            let parent = self.lookup(parent)?;
+
+            // the child's keys override the parent's
+            let (Yaml::Hash(parent), Yaml::Hash(child)) = (parent, &value) else {
+                return Err(invalid(item));
+            };
+            let mut merged = parent.clone();
+            for (key, child_value) in child {
+                merged.insert(key.clone(), child_value.clone());
+            }
+            value = Yaml::Hash(merged);
+        }
+
+        self.defines.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    // only names defined before this point resolve, which rules out cycles
+    fn resolve(&self, value: &Yaml) -> Result<Yaml, SceneError> {
+        match value {
+            Yaml::String(name) => self.lookup(name).cloned(),
+            // transform lists splice in the steps of the defines they name
+            Yaml::Array(steps) => {
+                let mut resolved = Vec::new();
+
+                for step in steps {
+                    match step {
+                        Yaml::String(name) => match self.lookup(name)? {
+                            Yaml::Array(inner) => resolved.extend(inner.iter().cloned()),
+                            _ => return Err(invalid(step)),
+                        },
+                        _ => resolved.push(step.clone()),
+                    }
+                }
+
+                Ok(Yaml::Array(resolved))
+            }
+            _ => Ok(value.clone()),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Result<&Yaml, SceneError> {
+        self.defines.get(name).ok_or_else(|| invalid(name))
+    }
+
+    fn material(&self, value: &Yaml) -> Result<Material, SceneError> {
+        if let Some(name) = value.as_str() {
+            return self.material(self.lookup(name)?);
+        }
+
+        let mut material = Material::new();
+
+        for (key, value) in value.as_hash().ok_or_else(|| invalid(value))? {
+            match key.as_str() {
+                Some("color") => {
+                    let (r, g, b) = xyz(value)?;
+                    material.color = color!(r, g, b);
+                }
+                Some("ambient") => material.ambient = number(value)?,
+                Some("diffuse") => material.diffuse = number(value)?,
+                Some("specular") => material.specular = number(value)?,
+                Some("shininess") => material.shininess = number(value)?,
+                Some("reflective") => material.reflective = number(value)?,
+                Some("transparency") => material.transparency = number(value)?,
+                Some("refractive-index") => material.refractive_index = number(value)?,
+                _ => return Err(invalid(key)),
+            }
+        }
+
+        Ok(material)
+    }
+
+    // each step is applied after the ones before it
+    fn transform(&self, value: &Yaml) -> Result<Matrix<4>, SceneError> {
+        let mut matrix = Matrix::identity();
+
+        for step in value.as_vec().ok_or_else(|| invalid(value))? {
+            let step_matrix = match step {
+                Yaml::String(name) => self.transform(self.lookup(name)?)?,
+                Yaml::Array(args) => {
+                    let (operation, args) = args.split_first().ok_or_else(|| invalid(step))?;
+
+                    match operation.as_str() {
+                        Some("translate") => {
+                            let [x, y, z] = numbers(args)?;
+                            translation(x, y, z)
+                        }
+                        Some("scale") => {
+                            let [x, y, z] = numbers(args)?;
+                            scaling(x, y, z)
+                        }
+                        Some("rotate-x") => rotation(Axis::X, numbers::<1>(args)?[0]),
+                        Some("rotate-y") => rotation(Axis::Y, numbers::<1>(args)?[0]),
+                        Some("rotate-z") => rotation(Axis::Z, numbers::<1>(args)?[0]),
+                        Some("shear") => {
+                            let [xy, xz, yx, yz, zx, zy] = numbers(args)?;
+                            shearing(xy, xz, yx, yz, zy, zx)
+                        }
+                        _ => return Err(invalid(step)),
+                    }
+                }
+                _ => return Err(invalid(step)),
+            };
+
+            matrix = step_matrix * matrix;
+        }
+
+        Ok(matrix)
+    }
+
+    fn shape(&self, item: &Yaml) -> Result<Box<dyn Shape>, SceneError> {
+        let limits = |mut minimum: f64, mut maximum: f64, mut closed: bool| {
+            if !item["min"].is_badvalue() {
+                minimum = number(&item["min"])?;
+            }
+            if !item["max"].is_badvalue() {
+                maximum = number(&item["max"])?;
+            }
+            if !item["closed"].is_badvalue() {
+                closed = item["closed"].as_bool().ok_or_else(|| invalid(item))?;
+            }
+            Ok::<_, SceneError>((minimum, maximum, closed))
+        };
+
+        let mut shape: Box<dyn Shape> = match item["add"].as_str() {
+            Some("sphere") => Box::new(Sphere::new()),
+            Some("plane") => Box::new(Plane::new()),
+            Some("cube") => Box::new(Cube::new()),
+            Some("cylinder") => {
+                let mut cylinder = Cylinder::new();
+                (cylinder.minimum, cylinder.maximum, cylinder.closed) =
+                    limits(cylinder.minimum, cylinder.maximum, cylinder.closed)?;
+                Box::new(cylinder)
+            }
+            Some("cone") => {
+                let mut cone = Cone::new();
+                (cone.minimum, cone.maximum, cone.closed) =
+                    limits(cone.minimum, cone.maximum, cone.closed)?;
+                Box::new(cone)
+            }
+            Some("group") => {
+                let mut group = Group::new();
+                if let Some(children) = item["children"].as_vec() {
+                    for child in children {
+                        group.add_child(self.shape(child)?);
+                    }
+                }
+                Box::new(group)
+            }
+            _ => return Err(invalid(item)),
+        };
+
+        if !item["material"].is_badvalue() {
+            *shape.material_mut() = self.material(&item["material"])?;
+        }
+        if !item["transform"].is_badvalue() {
+            shape.set_transform(self.transform(&item["transform"])?);
+        }
+
+        Ok(shape)
+    }
+
+    fn add(&mut self, item: &Yaml) -> Result<(), SceneError> {
+        match item["add"].as_str() {
+            Some("camera") => {
+                let width = size(&item["width"])?;
+                let height = size(&item["height"])?;
+                let (fx, fy, fz) = xyz(&item["from"])?;
+                let (tx, ty, tz) = xyz(&item["to"])?;
+                let (ux, uy, uz) = xyz(&item["up"])?;
+
+                let transform =
+                    view_transform(point!(fx, fy, fz), point!(tx, ty, tz), vector!(ux, uy, uz));
+                // up along the line of sight leaves no way to orient the view
+                if transform.inverse().is_none() {
+                    return Err(invalid(item));
+                }
+
+                let mut camera = Camera::new(width, height, number(&item["field-of-view"])?);
+                camera.set_transform(transform);
+                self.camera = Some(camera);
+            }
+            Some("light") => {
+                let (x, y, z) = xyz(&item["at"])?;
+                let (r, g, b) = xyz(&item["intensity"])?;
+                self.world.lights.push(Light::from(PointLight::new(
+                    point!(x, y, z),
+                    color!(r, g, b),
+                )));
+            }
+            _ => {
+                let shape = self.shape(item)?;
+                self.world.objects.push(shape);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn parse_scene(input: &str) -> Result<(Camera, World), SceneError> {
+    let documents = YamlLoader::load_from_str(input)?;
+    let items = match documents.first() {
+        Some(Yaml::Array(items)) => items.as_slice(),
+        Some(document) => return Err(invalid(document)),
+        None => &[],
+    };
+
+    let mut builder = SceneBuilder {
+        defines: HashMap::new(),
+        camera: None,
+        world: World::new(),
+    };
+
+    for item in items {
+        if !item["define"].is_badvalue() {
+            builder.define(item)?;
+        } else {
+            builder.add(item)?;
+        }
+    }
+
+    // anything that would only panic once rendering starts is rejected here
+    let fatal = builder.world.validate().into_iter().find(|warning| {
+        matches!(
+            warning,
+            SceneWarning::NanTransform(_) | SceneWarning::SingularTransform(_)
+        )
+    });
+    if let Some(warning) = fatal {
+        return Err(invalid(warning));
+    }
+
+    let camera = builder.camera.ok_or(SceneError::MissingCamera)?;
+    Ok((camera, builder.world))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rays::Ray;
+    use std::f64::consts::PI;
+
+    const SCENE: &str = "
+- add: camera
+  width: 100
+  height: 50
+  field-of-view: 0.785
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+  up: [0, 1, 0]
+
+- add: light
+  at: [-10, 10, -10]
+  intensity: [1, 1, 1]
+
+- define: white-material
+  value:
+    color: [1, 1, 1]
+    diffuse: 0.7
+    specular: 0.3
+
+- define: blue-material
+  extend: white-material
+  value:
+    color: [0.2, 0.4, 0.9]
+
+- define: standard-transform
+  value:
+    - [translate, 1, -1, 1]
+    - [scale, 0.5, 0.5, 0.5]
+
+- add: sphere
+  material: blue-material
+  transform:
+    - standard-transform
+    - [rotate-y, 1.5707963267948966]
+";
+
+    #[test]
+    fn test_parse_scene() {
+        let (camera, world) = parse_scene(SCENE).unwrap();
+
+        assert_eq!(100, camera.hsize());
+        assert_eq!(50, camera.vsize());
+        assert_eq!(0.785, camera.field_of_view());
+
+        assert_eq!(
+            vec![Light::from(PointLight::new(
+                point!(-10, 10, -10),
+                color!(1, 1, 1)
+            ))],
+            world.lights
+        );
+
+        assert_eq!(1, world.objects.len());
+        let sphere = &world.objects[0];
+
+        let material = sphere.material();
+        assert_eq!(color!(0.2, 0.4, 0.9), material.color);
+        assert_eq!(0.7, material.diffuse);
+        assert_eq!(0.3, material.specular);
+        assert_eq!(0.1, material.ambient);
+
+        let expected =
+            rotation(Axis::Y, PI / 2.0) * scaling(0.5, 0.5, 0.5) * translation(1.0, -1.0, 1.0);
+        assert_eq!(&expected, sphere.transform());
+
+        let ray = Ray::new(point!(0.5, -0.5, -5), vector!(0, 0, 1));
+        assert_eq!(2, world.intersect(&ray).0.len());
+    }
+
+    const CAMERA: &str = "- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+  up: [0, 1, 0]
+";
+
+    #[test]
+    fn test_parse_scene_errors() {
+        assert!(matches!(
+            parse_scene("- add: light\n  at: [0, 0, 0]\n  intensity: [1, 1, 1]"),
+            Err(SceneError::MissingCamera)
+        ));
+        assert!(matches!(
+            parse_scene("- add: teapot"),
+            Err(SceneError::Invalid(_))
+        ));
+        assert!(matches!(
+            parse_scene("- add: sphere\n  material: missing"),
+            Err(SceneError::Invalid(_))
+        ));
+        assert!(matches!(
+            parse_scene("- [unclosed"),
+            Err(SceneError::Yaml(_))
+        ));
+
+        assert!(parse_scene(CAMERA).is_ok());
+        for size in ["-1", "0", "1.5"] {
+            let scene = CAMERA.replace("width: 10", &format!("width: {size}"));
+            assert!(matches!(parse_scene(&scene), Err(SceneError::Invalid(_))));
+        }
+
+        // looking straight along up
+        let scene = CAMERA.replace("up: [0, 1, 0]", "up: [0, 0, 1]");
+        assert!(matches!(parse_scene(&scene), Err(SceneError::Invalid(_))));
+
+        let scene = format!("{CAMERA}- add: sphere\n  transform: [[scale, 0, 0, 0]]\n");
+        assert!(matches!(parse_scene(&scene), Err(SceneError::Invalid(_))));
+
+        // a define can't refer to itself or anything defined after it
+        let scene = format!("{CAMERA}- define: a\n  value: [a]\n- add: sphere\n  transform: [a]\n");
+        assert!(matches!(parse_scene(&scene), Err(SceneError::Invalid(_))));
+
+        let scene = format!(
+            "{CAMERA}- define: a\n  value: [[scale, 2, 2, 2]]
+- define: b\n  value: [a, [translate, 1, 0, 0]]
+- define: a\n  value: [b]
+- add: sphere\n  transform: [a]\n"
+        );
+        let (_, world) = parse_scene(&scene).unwrap();
+        assert_eq!(
+            &(translation(1.0, 0.0, 0.0) * scaling(2.0, 2.0, 2.0)),
+            world.objects[0].transform()
+        );
+    }
+}
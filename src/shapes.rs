@@ -0,0 +1,281 @@
+use crate::{
+    bounds::AABB,
+    intersections::{Intersection, Intersections},
+    materials::Material,
+    matrices::Matrix,
+    rays::Ray,
+    tuples::Tuple,
+};
+use std::{any::Any, fmt::Debug, ptr};
+
+// Sync so a &World can be shared across render threads, Any so callers can downcast
+pub trait Shape: Any + Debug + Sync {
+    fn transform(&self) -> &Matrix<4>;
+
+    fn set_transform(&mut self, transform: Matrix<4>);
+
+    fn material(&self) -> &Material;
+
+    fn material_mut(&mut self) -> &mut Material;
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_>;
+
+    // groups and csg shapes have no surface of their own. Hits record the child that was struck,
+    // so World never asks a composite for its normal; called directly, it answers with the normal
+    // of the child whose bounds hold the point (see child_normal_at)
+    fn local_normal_at(&self, point: &Tuple) -> Tuple;
+
+    // in object space
+    fn bounds(&self) -> AABB;
+
+    // cached by set_transform, since every ray and normal needs them; None when the transform
+    // is singular
+    fn inverse(&self) -> Option<Matrix<4>>;
+
+    fn inverse_transpose(&self) -> Option<Matrix<4>>;
+
+    // only groups have children to subdivide
+    fn divide(&mut self, _threshold: usize) {}
+
+    // composites pass the material down, since hits report the child that was struck
+    fn set_material(&mut self, material: Material) {
+        *self.material_mut() = material;
+    }
+
+    // whether other is this shape or one of its descendants
+    fn includes(&self, other: &dyn Shape) -> bool {
+        ptr::addr_eq(self, other)
+    }
+
+    fn intersect(&self, ray: &Ray) -> Intersections<'_> {
+        match self.inverse() {
+            Some(inverse) => self.local_intersect(&ray.transform(&inverse)),
+            // a singular transform squashes the shape flat, leaving nothing for a ray to hit
+            None => Intersections::empty(),
+        }
+    }
+
+    // a singular shape is never hit, so asking about its surface leaves the point as it is
+    fn world_to_object(&self, point: &Tuple) -> Tuple {
+        match self.inverse() {
+            Some(inverse) => inverse * *point,
+            None => *point,
+        }
+    }
+
+    fn normal_at(&self, point: &Tuple) -> Tuple {
+        let object_point = self.world_to_object(point);
+
+        self.normal_to_world(&self.local_normal_at(&object_point))
+    }
+
+    // shapes whose normal depends on where the hit landed override this
+    fn local_normal_at_hit(&self, point: &Tuple, _hit: &Intersection) -> Tuple {
+        self.local_normal_at(point)
+    }
+
+    fn normal_at_hit(&self, point: &Tuple, hit: &Intersection) -> Tuple {
+        let object_point = self.world_to_object(point);
+
+        self.normal_to_world(&self.local_normal_at_hit(&object_point, hit))
+    }
+
+    fn normal_to_world(&self, normal: &Tuple) -> Tuple {
+        let mut world_normal = match self.inverse_transpose() {
+            Some(inverse_transpose) => inverse_transpose * *normal,
+            None => *normal,
+        };
+        world_normal.3 = 0.0;
+        world_normal.normalize()
+    }
+}
+
+// a shape's transform along with its inverse, worked out once when the transform is set
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    matrix: Matrix<4>,
+    // None when the matrix is singular
+    inverse: Option<Matrix<4>>,
+    inverse_transpose: Option<Matrix<4>>,
+}
+
+impl Transform {
+    pub const fn identity() -> Self {
+        Self {
+            matrix: Matrix::<4>::identity(),
+            inverse: Some(Matrix::<4>::identity()),
+            inverse_transpose: Some(Matrix::<4>::identity()),
+        }
+    }
+
+    pub fn new(matrix: Matrix<4>) -> Self {
+        let inverse = matrix.inverse();
+
+        Self {
+            matrix,
+            inverse,
+            inverse_transpose: inverse.map(|inverse| inverse.transpose()),
+        }
+    }
+
+    pub fn matrix(&self) -> &Matrix<4> {
+        &self.matrix
+    }
+
+    pub fn inverse(&self) -> Option<Matrix<4>> {
+        self.inverse
+    }
+
+    pub fn inverse_transpose(&self) -> Option<Matrix<4>> {
+        self.inverse_transpose
+    }
+}
+
+// point and normal are in world space, where composites keep their children. A bare point doesn't
+// say which surface it came from, so the first child whose bounds hold it wins, falling back to
+// the first child
+pub(crate) fn child_normal_at<'a>(
+    mut children: impl Iterator<Item = &'a dyn Shape> + Clone,
+    point: &Tuple,
+) -> Option<Tuple> {
+    let first = children.clone().next();
+
+    children
+        .find(|child| {
+            child
+                .bounds()
+                .transform(child.transform())
+                .contains_point(point)
+        })
+        .or(first)
+        .map(|child| child.normal_at(point))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transformations::*;
+    use std::{
+        f64::consts::{FRAC_1_SQRT_2, PI},
+        sync::Mutex,
+    };
+
+    #[derive(Debug)]
+    struct TestShape {
+        transform: Transform,
+        material: Material,
+        saved_ray: Mutex<Option<Ray>>,
+    }
+
+    impl TestShape {
+        fn new() -> Self {
+            Self {
+                transform: Transform::identity(),
+                material: Material::new(),
+                saved_ray: Mutex::new(None),
+            }
+        }
+    }
+
+    impl Shape for TestShape {
+        fn transform(&self) -> &Matrix<4> {
+            self.transform.matrix()
+        }
+
+        fn set_transform(&mut self, transform: Matrix<4>) {
+            self.transform = Transform::new(transform);
+        }
+
+        fn inverse(&self) -> Option<Matrix<4>> {
+            self.transform.inverse()
+        }
+
+        fn inverse_transpose(&self) -> Option<Matrix<4>> {
+            self.transform.inverse_transpose()
+        }
+
+        fn material(&self) -> &Material {
+            &self.material
+        }
+
+        fn material_mut(&mut self) -> &mut Material {
+            &mut self.material
+        }
+
+        fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+            *self.saved_ray.lock().unwrap() = Some(*ray);
+            Intersections::empty()
+        }
+
+        fn local_normal_at(&self, point: &Tuple) -> Tuple {
+            vector!(point.0, point.1, point.2)
+        }
+
+        fn bounds(&self) -> AABB {
+            AABB::new(point!(-1, -1, -1), point!(1, 1, 1))
+        }
+    }
+
+    #[test]
+    fn test_defaults() {
+        let mut shape = TestShape::new();
+        assert_eq!(&Matrix::<4>::identity(), shape.transform());
+        assert_eq!(&Material::new(), shape.material());
+
+        shape.set_transform(translation(2.0, 3.0, 4.0));
+        assert_eq!(&translation(2.0, 3.0, 4.0), shape.transform());
+
+        shape.material_mut().ambient = 1.0;
+        assert_eq!(1.0, shape.material().ambient);
+    }
+
+    #[test]
+    fn test_intersect() {
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+
+        let mut shape = TestShape::new();
+        shape.set_transform(scaling(2.0, 2.0, 2.0));
+        shape.intersect(&ray);
+        let saved_ray = shape.saved_ray.lock().unwrap().unwrap();
+        assert_eq!(point!(0, 0, -2.5), saved_ray.origin);
+        assert_eq!(vector!(0, 0, 0.5), saved_ray.direction);
+
+        let mut shape = TestShape::new();
+        shape.set_transform(translation(5.0, 0.0, 0.0));
+        shape.intersect(&ray);
+        let saved_ray = shape.saved_ray.lock().unwrap().unwrap();
+        assert_eq!(point!(-5, 0, -5), saved_ray.origin);
+        assert_eq!(vector!(0, 0, 1), saved_ray.direction);
+    }
+
+    #[test]
+    fn test_singular_transform() {
+        let mut shape = TestShape::new();
+        shape.set_transform(scaling(1.0, 0.0, 1.0));
+        assert_eq!(None, shape.inverse());
+        assert_eq!(None, shape.inverse_transpose());
+
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+        assert_eq!(0, shape.intersect(&ray).count());
+        assert!(shape.saved_ray.lock().unwrap().is_none());
+
+        assert_eq!(vector!(0, 1, 0), shape.normal_at(&point!(0, 1, 0)));
+    }
+
+    #[test]
+    fn test_normal_at() {
+        let mut shape = TestShape::new();
+        shape.set_transform(translation(0.0, 1.0, 0.0));
+        assert_eq!(
+            vector!(0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+            shape.normal_at(&point!(0, 1.0 + FRAC_1_SQRT_2, -FRAC_1_SQRT_2))
+        );
+
+        let mut shape = TestShape::new();
+        shape.set_transform(scaling(1.0, 0.5, 1.0) * rotation(Axis::Z, PI / 5.0));
+        assert_eq!(
+            vector!(0, 0.97014, -0.24254),
+            shape.normal_at(&point!(0, 2f64.sqrt() / 2.0, -2f64.sqrt() / 2.0))
+        );
+    }
+}
@@ -1,13 +1,19 @@
 use crate::{
-    constants::EPSILON, intersections::Intersections, materials::Material, matrices::Matrix,
-    rays::Ray, tuples::*,
+    bounds::AABB,
+    constants::EPSILON,
+    intersections::Intersections,
+    materials::Material,
+    matrices::Matrix,
+    rays::Ray,
+    shapes::{Shape, Transform},
+    tuples::*,
 };
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct Sphere {
     origin: Tuple,
     radius: f64,
-    transform: Matrix<4>,
+    transform: Transform,
     pub material: Material,
 }
 
@@ -22,14 +28,90 @@ impl Sphere {
         Self {
             origin: Tuple::new(0.0, 0.0, 0.0, 1.0),
             radius: 1.0,
-            transform: Matrix::<4>::identity(),
+            transform: Transform::identity(),
             material: Material::new(),
         }
     }
 
-    pub fn intersect(&self, ray: &Ray) -> Intersections {
-        let ray = ray.transform(&self.transform.inverse().unwrap());
+    pub const fn glass() -> Self {
+        let mut sphere = Self::new();
+        sphere.material.transparency = 1.0;
+        sphere.material.refractive_index = 1.5;
+        sphere
+    }
 
+    pub fn instanced_at(&self, transform: Matrix<4>) -> Self {
+        let mut instance = self.clone();
+        instance.set_transform(transform);
+        instance
+    }
+}
+
+// the cached inverses are rebuilt from transform when loading
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SphereData {
+    origin: Tuple,
+    radius: f64,
+    transform: Matrix<4>,
+    material: Material,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Sphere {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SphereData {
+            origin: self.origin,
+            radius: self.radius,
+            transform: *self.transform.matrix(),
+            material: self.material.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Sphere {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = SphereData::deserialize(deserializer)?;
+        let mut sphere = Sphere {
+            origin: data.origin,
+            radius: data.radius,
+            material: data.material,
+            ..Sphere::new()
+        };
+        sphere.set_transform(data.transform);
+
+        Ok(sphere)
+    }
+}
+
+impl Shape for Sphere {
+    fn transform(&self) -> &Matrix<4> {
+        self.transform.matrix()
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = Transform::new(transform);
+    }
+
+    fn inverse(&self) -> Option<Matrix<4>> {
+        self.transform.inverse()
+    }
+
+    fn inverse_transpose(&self) -> Option<Matrix<4>> {
+        self.transform.inverse_transpose()
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
         let sphere_to_ray = ray.origin - self.origin;
 
         let a = ray.direction.dot_product(ray.direction);
@@ -47,25 +129,28 @@ impl Sphere {
         let diff = t1 - t2;
 
         if diff.abs() < EPSILON {
-            Intersections::from(&[t1], *self)
+            Intersections::from(&[t1], self)
         } else if diff < 0.0 {
-            Intersections::from(&[t1, t2], *self)
+            Intersections::from(&[t1, t2], self)
         } else {
-            Intersections::from(&[t2, t1], *self)
+            Intersections::from(&[t2, t1], self)
         }
     }
 
-    pub fn set_transform(&mut self, transform: Matrix<4>) {
-        self.transform = transform;
+    fn local_normal_at(&self, point: &Tuple) -> Tuple {
+        *point - self.origin
+    }
+
+    fn bounds(&self) -> AABB {
+        let radius = vector!(self.radius, self.radius, self.radius);
+
+        AABB::new(self.origin - radius, self.origin + radius)
     }
+}
 
-    pub fn normal_at(&self, point: &Tuple) -> Tuple {
-        let inverse_transform = self.transform.inverse().unwrap();
-        let object_point = inverse_transform * *point;
-        let object_normal = object_point - self.origin;
-        let mut world_normal = inverse_transform.transpose() * object_normal;
-        world_normal.3 = 0.0;
-        world_normal.normalize()
+impl Default for Sphere {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -74,7 +159,7 @@ mod test {
     use super::*;
     use crate::intersections::Intersection;
     use crate::transformations::*;
-    use std::f64::consts::PI;
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
 
     #[test]
     fn test_intersect() {
@@ -82,8 +167,8 @@ mod test {
         let sphere = Sphere::new();
         assert_eq!(
             Intersections(vec![
-                Intersection::new(4.0, sphere),
-                Intersection::new(6.0, sphere),
+                Intersection::new(4.0, &sphere),
+                Intersection::new(6.0, &sphere),
             ]),
             sphere.intersect(&ray)
         );
@@ -91,7 +176,7 @@ mod test {
         let ray = Ray::new(point!(0, 1, -5), vector!(0, 0, 1));
         let sphere = Sphere::new();
         assert_eq!(
-            Intersections(vec![Intersection::new(5.0, sphere)]),
+            Intersections(vec![Intersection::new(5.0, &sphere)]),
             sphere.intersect(&ray)
         );
 
@@ -103,8 +188,8 @@ mod test {
         let sphere = Sphere::new();
         assert_eq!(
             Intersections(vec![
-                Intersection::new(-1.0, sphere),
-                Intersection::new(1.0, sphere),
+                Intersection::new(-1.0, &sphere),
+                Intersection::new(1.0, &sphere),
             ]),
             sphere.intersect(&ray)
         );
@@ -113,8 +198,8 @@ mod test {
         let sphere = Sphere::new();
         assert_eq!(
             Intersections(vec![
-                Intersection::new(-6.0, sphere),
-                Intersection::new(-4.0, sphere),
+                Intersection::new(-6.0, &sphere),
+                Intersection::new(-4.0, &sphere),
             ]),
             sphere.intersect(&ray)
         );
@@ -124,21 +209,77 @@ mod test {
         sphere.set_transform(scaling(2.0, 2.0, 2.0));
         assert_eq!(
             Intersections(vec![
-                Intersection::new(3.0, sphere),
-                Intersection::new(7.0, sphere),
+                Intersection::new(3.0, &sphere),
+                Intersection::new(7.0, &sphere),
             ]),
             sphere.intersect(&ray)
         );
     }
 
+    #[test]
+    fn test_glass() {
+        let sphere = Sphere::glass();
+
+        assert_eq!(Matrix::<4>::identity(), *sphere.transform());
+        assert_eq!(1.0, sphere.material.transparency);
+        assert_eq!(1.5, sphere.material.refractive_index);
+    }
+
     #[test]
     fn test_transform() {
         let mut sphere = Sphere::new();
 
-        assert_eq!(Matrix::<4>::identity(), sphere.transform);
+        assert_eq!(Matrix::<4>::identity(), *sphere.transform());
 
         sphere.set_transform(translation(2.0, 3.0, 4.0));
-        assert_eq!(translation(2.0, 3.0, 4.0), sphere.transform);
+        assert_eq!(translation(2.0, 3.0, 4.0), *sphere.transform());
+    }
+
+    #[test]
+    fn test_cached_inverse() {
+        let mut sphere = Sphere::new();
+        assert_eq!(Some(Matrix::<4>::identity()), sphere.inverse());
+        assert_eq!(Some(Matrix::<4>::identity()), sphere.inverse_transpose());
+
+        let transform = scaling(1.0, 0.5, 1.0) * rotation(Axis::Z, PI / 5.0);
+        sphere.set_transform(transform);
+        assert_eq!(transform.inverse(), sphere.inverse());
+        assert_eq!(
+            transform.inverse().map(|inverse| inverse.transpose()),
+            sphere.inverse_transpose()
+        );
+
+        sphere.set_transform(translation(2.0, 3.0, 4.0));
+        assert_eq!(Some(translation(-2.0, -3.0, -4.0)), sphere.inverse());
+
+        sphere.set_transform(scaling(0.0, 0.0, 0.0));
+        assert_eq!(None, sphere.inverse());
+        assert_eq!(None, sphere.inverse_transpose());
+
+        // squashed flat, so there's nothing to hit
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+        assert_eq!(0, sphere.intersect(&ray).count());
+    }
+
+    #[test]
+    fn test_instanced_at() {
+        let mut sphere = Sphere::new();
+        sphere.material.color = color!(1, 0, 0);
+        sphere.material.specular = 1.0;
+        sphere.set_transform(scaling(2.0, 2.0, 2.0));
+
+        let instance = sphere.instanced_at(translation(5.0, 0.0, 0.0));
+
+        assert_eq!(sphere.material, instance.material);
+        assert_eq!(translation(5.0, 0.0, 0.0), *instance.transform());
+        assert_eq!(scaling(2.0, 2.0, 2.0), *sphere.transform());
+
+        let ray = Ray::new(point!(5, 0, -5), vector!(0, 0, 1));
+        assert_eq!(
+            Intersections::from(&[4.0, 6.0], &instance),
+            instance.intersect(&ray)
+        );
+        assert_eq!(vector!(1, 0, 0), instance.normal_at(&point!(6, 0, 0)));
     }
 
     #[test]
@@ -159,8 +300,8 @@ mod test {
 
         sphere.set_transform(translation(0.0, 1.0, 0.0));
         assert_eq!(
-            vector!(0, 0.70711, -0.70711),
-            sphere.normal_at(&point!(0, 1.70711, -0.70711))
+            vector!(0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+            sphere.normal_at(&point!(0, 1.0 + FRAC_1_SQRT_2, -FRAC_1_SQRT_2))
         );
 
         sphere.set_transform(scaling(1.0, 0.5, 1.0) * rotation(Axis::Z, PI / 5.0));
@@ -169,4 +310,117 @@ mod test {
             sphere.normal_at(&point!(0, 2f64.sqrt() / 2.0, -2f64.sqrt() / 2.0))
         );
     }
+
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> f64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        fn between(&mut self, min: f64, max: f64) -> f64 {
+            min + (max - min) * self.next()
+        }
+    }
+
+    fn assert_invariants(xs: &Intersections) {
+        assert!(xs.0.iter().all(|i| i.t.is_finite()), "{xs:?}");
+        assert!(xs.0.windows(2).all(|pair| pair[0].t <= pair[1].t), "{xs:?}");
+
+        let nearest =
+            xs.0.iter()
+                .map(|i| i.t)
+                .filter(|t| *t >= 0.0)
+                .reduce(f64::min);
+        assert_eq!(nearest, xs.hit().map(|hit| hit.t));
+    }
+
+    #[test]
+    fn test_intersect_random_rays() {
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        let outer = Sphere::new();
+        let mut inner = Sphere::new();
+        inner.set_transform(scaling(0.5, 0.5, 0.5));
+
+        for _ in 0..10_000 {
+            let origin = point!(
+                rng.between(-5.0, 5.0),
+                rng.between(-5.0, 5.0),
+                rng.between(-5.0, 5.0)
+            );
+            let target = point!(
+                rng.between(-1.2, 1.2),
+                rng.between(-1.2, 1.2),
+                rng.between(-1.2, 1.2)
+            );
+            if (target - origin).magnitude() < EPSILON {
+                continue;
+            }
+
+            let ray = Ray::new(origin, (target - origin).normalize());
+
+            assert_invariants(&outer.intersect(&ray));
+            assert_invariants(&inner.intersect(&ray));
+        }
+    }
+
+    #[test]
+    fn test_intersect_regressions() {
+        let sphere = Sphere::new();
+
+        let tangent = Ray::new(point!(0, 1, -5), vector!(0, 0, 1));
+        let xs = sphere.intersect(&tangent);
+        assert_invariants(&xs);
+        assert_eq!(5.0, xs.hit().unwrap().t);
+
+        let tangent = Ray::new(point!(1, 0, -5), vector!(0, 0, 1));
+        let xs = sphere.intersect(&tangent);
+        assert_invariants(&xs);
+        assert_eq!(1, xs.count());
+
+        let on_surface = Ray::new(point!(0, 0, -1), vector!(0, 0, 1));
+        let xs = sphere.intersect(&on_surface);
+        assert_invariants(&xs);
+        assert_eq!(Intersections::from(&[0.0, 2.0], &sphere), xs);
+
+        let leaving_surface = Ray::new(point!(0, 0, 1), vector!(0, 0, 1));
+        let xs = sphere.intersect(&leaving_surface);
+        assert_invariants(&xs);
+        assert_eq!(Intersections::from(&[-2.0, 0.0], &sphere), xs);
+
+        let mut scaled = Sphere::new();
+        scaled.set_transform(scaling(0.5, 0.5, 0.5));
+        let on_scaled_surface = Ray::new(point!(0.5, 0, 0), vector!(-1, 0, 0));
+        let xs = scaled.intersect(&on_scaled_surface);
+        assert_invariants(&xs);
+        assert_eq!(Intersections::from(&[0.0, 1.0], &scaled), xs);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let sphere = Sphere::new();
+
+        assert_eq!(
+            AABB::new(point!(-1, -1, -1), point!(1, 1, 1)),
+            sphere.bounds()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut sphere = Sphere::glass();
+        sphere.set_transform(translation(1.0, 2.0, 3.0) * scaling(0.5, 0.5, 0.5));
+        sphere.material.color = color!(0.1, 0.2, 0.3);
+
+        let json = serde_json::to_string(&sphere).unwrap();
+        let loaded: Sphere = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(sphere.transform(), loaded.transform());
+        assert_eq!(sphere.inverse(), loaded.inverse());
+        assert_eq!(sphere.material, loaded.material);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::matrices::Matrix;
+use crate::{matrices::Matrix, tuples::Tuple};
 
 pub const fn translation(x: f64, y: f64, z: f64) -> Matrix<4> {
     Matrix::new([
@@ -9,9 +9,10 @@ pub const fn translation(x: f64, y: f64, z: f64) -> Matrix<4> {
     ])
 }
 
+#[macro_export]
 macro_rules! translate {
     ($x:expr, $y:expr, $z:expr) => {
-        translation($x as f64, $y as f64, $z as f64)
+        $crate::transformations::translation($x as f64, $y as f64, $z as f64)
     };
 }
 
@@ -24,9 +25,10 @@ pub const fn scaling(x: f64, y: f64, z: f64) -> Matrix<4> {
     ])
 }
 
+#[macro_export]
 macro_rules! scale {
     ($x:expr, $y:expr, $z:expr) => {
-        scaling($x as f64, $y as f64, $z as f64)
+        $crate::transformations::scaling($x as f64, $y as f64, $z as f64)
     };
 }
 
@@ -40,8 +42,8 @@ pub fn rotation(axis: Axis, radians: f64) -> Matrix<4> {
     match axis {
         Axis::X => Matrix::new([
             [1.0, 0.0, 0.0, 0.0],
-            [1.0, radians.cos(), -radians.sin(), 0.0],
-            [1.0, radians.sin(), radians.cos(), 0.0],
+            [0.0, radians.cos(), -radians.sin(), 0.0],
+            [0.0, radians.sin(), radians.cos(), 0.0],
             [0.0, 0.0, 0.0, 1.0],
         ]),
         Axis::Y => Matrix::new([
@@ -59,24 +61,55 @@ pub fn rotation(axis: Axis, radians: f64) -> Matrix<4> {
     }
 }
 
+#[macro_export]
 macro_rules! rotate_x {
     ($radians:expr) => {
-        rotation(Axis::X, $radians)
+        $crate::transformations::rotation($crate::transformations::Axis::X, $radians)
     };
 }
 
+#[macro_export]
 macro_rules! rotate_y {
     ($radians:expr) => {
-        rotation(Axis::Y, $radians)
+        $crate::transformations::rotation($crate::transformations::Axis::Y, $radians)
     };
 }
 
+#[macro_export]
 macro_rules! rotate_z {
     ($radians:expr) => {
-        rotation(Axis::Z, $radians)
+        $crate::transformations::rotation($crate::transformations::Axis::Z, $radians)
     };
 }
 
+pub fn scaling_about(center: Tuple, x: f64, y: f64, z: f64) -> Matrix<4> {
+    let Tuple(cx, cy, cz, _) = center;
+
+    translation(cx, cy, cz) * scaling(x, y, z) * translation(-cx, -cy, -cz)
+}
+
+pub fn rotation_about(center: Tuple, axis: Axis, radians: f64) -> Matrix<4> {
+    let Tuple(cx, cy, cz, _) = center;
+
+    translation(cx, cy, cz) * rotation(axis, radians) * translation(-cx, -cy, -cz)
+}
+
+pub fn view_transform(from: Tuple, to: Tuple, up: Tuple) -> Matrix<4> {
+    let forward = (to - from).normalize();
+    let left = forward.cross_product(up.normalize());
+    let true_up = left.cross_product(forward);
+    let Tuple(fx, fy, fz, _) = from;
+
+    let orientation = Matrix::new([
+        [left.0, left.1, left.2, 0.0],
+        [true_up.0, true_up.1, true_up.2, 0.0],
+        [-forward.0, -forward.1, -forward.2, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+
+    orientation * translation(-fx, -fy, -fz)
+}
+
 pub fn shearing(
     x_to_y: f64,
     x_to_z: f64,
@@ -93,6 +126,7 @@ pub fn shearing(
     ])
 }
 
+#[macro_export]
 macro_rules! shear {
     (
     $x_to_y:expr,
@@ -102,13 +136,13 @@ macro_rules! shear {
     $z_to_y:expr,
     $z_to_x:expr
     ) => {
-        shearing(
+        $crate::transformations::shearing(
             $x_to_y as f64,
             $x_to_z as f64,
             $y_to_x as f64,
             $y_to_z as f64,
-            $z_to_x as f64,
             $z_to_y as f64,
+            $z_to_x as f64,
         )
     };
 }
@@ -116,7 +150,6 @@ macro_rules! shear {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::*;
 
     #[test]
     fn test_translation() {
@@ -165,6 +198,19 @@ mod test {
             point!(0, 2f64.sqrt() / 2.0, -2f64.sqrt() / 2.0),
             half_quarter.inverse().unwrap() * point
         );
+
+        let point = point!(1, 1, 0);
+        assert_eq!(
+            point!(1, 2f64.sqrt() / 2.0, 2f64.sqrt() / 2.0),
+            half_quarter * point
+        );
+        assert_eq!(point!(1, 0, 1), full_quarter * point);
+        assert_eq!(point!(1, 0, -1), full_quarter.inverse().unwrap() * point);
+        assert_eq!(
+            point!(1, 1, 0),
+            full_quarter.inverse().unwrap() * full_quarter * point
+        );
+        assert_eq!(vector!(1, 0, 0), full_quarter * vector!(1, 0, 0));
     }
 
     #[test]
@@ -214,9 +260,81 @@ mod test {
         assert_eq!(point!(2, 7, 4), transform * point);
 
         let transform = shear!(0, 0, 0, 0, 1, 0);
-        assert_eq!(point!(2, 3, 6), transform * point);
+        assert_eq!(point!(2, 3, 7), transform * point);
 
         let transform = shear!(0, 0, 0, 0, 0, 1);
-        assert_eq!(point!(2, 3, 7), transform * point);
+        assert_eq!(point!(2, 3, 6), transform * point);
+    }
+
+    #[test]
+    fn test_shear_macro() {
+        assert_eq!(
+            shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0),
+            shear!(1, 0, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            shearing(0.0, 1.0, 0.0, 0.0, 0.0, 0.0),
+            shear!(0, 1, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            shearing(0.0, 0.0, 1.0, 0.0, 0.0, 0.0),
+            shear!(0, 0, 1, 0, 0, 0)
+        );
+        assert_eq!(
+            shearing(0.0, 0.0, 0.0, 1.0, 0.0, 0.0),
+            shear!(0, 0, 0, 1, 0, 0)
+        );
+        assert_eq!(
+            shearing(0.0, 0.0, 0.0, 0.0, 1.0, 0.0),
+            shear!(0, 0, 0, 0, 1, 0)
+        );
+        assert_eq!(
+            shearing(0.0, 0.0, 0.0, 0.0, 0.0, 1.0),
+            shear!(0, 0, 0, 0, 0, 1)
+        );
+    }
+
+    #[test]
+    fn test_scaling_about() {
+        let transform = scaling_about(point!(2, 0, 0), 2.0, 2.0, 2.0);
+
+        assert_eq!(point!(2, 0, 0), transform * point!(2, 0, 0));
+        assert_eq!(point!(4, 0, 0), transform * point!(3, 0, 0));
+        assert_eq!(point!(2, 2, 0), transform * point!(2, 1, 0));
+        assert_eq!(point!(0, -2, 2), transform * point!(1, -1, 1));
+    }
+
+    #[test]
+    fn test_rotation_about() {
+        use std::f64::consts::PI;
+
+        let transform = rotation_about(point!(2, 0, 0), Axis::Z, PI / 2.0);
+
+        assert_eq!(point!(2, 0, 0), transform * point!(2, 0, 0));
+        assert_eq!(point!(2, 1, 0), transform * point!(3, 0, 0));
+        assert_eq!(point!(1, 0, 0), transform * point!(2, 1, 0));
+    }
+
+    #[test]
+    fn test_view_transform() {
+        let transform = view_transform(point!(0, 0, 0), point!(0, 0, -1), vector!(0, 1, 0));
+        assert_eq!(Matrix::identity(), transform);
+
+        let transform = view_transform(point!(0, 0, 0), point!(0, 0, 1), vector!(0, 1, 0));
+        assert_eq!(scaling(-1.0, 1.0, -1.0), transform);
+
+        let transform = view_transform(point!(0, 0, 8), point!(0, 0, 0), vector!(0, 1, 0));
+        assert_eq!(translation(0.0, 0.0, -8.0), transform);
+
+        let transform = view_transform(point!(1, 3, 2), point!(4, -2, 8), vector!(1, 1, 0));
+        assert_eq!(
+            Matrix::new([
+                [-0.50709, 0.50709, 0.67612, -2.36643],
+                [0.76772, 0.60609, 0.12122, -2.82843],
+                [-0.35857, 0.59761, -0.71714, 0.00000],
+                [0.00000, 0.00000, 0.00000, 1.00000],
+            ]),
+            transform
+        );
     }
 }
@@ -0,0 +1,311 @@
+use crate::{
+    bounds::AABB,
+    constants::EPSILON,
+    intersections::{Intersection, Intersections},
+    materials::Material,
+    matrices::Matrix,
+    rays::Ray,
+    shapes::{Shape, Transform},
+    tuples::Tuple,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triangle {
+    p1: Tuple,
+    p2: Tuple,
+    p3: Tuple,
+    e1: Tuple,
+    e2: Tuple,
+    normal: Tuple,
+    transform: Transform,
+    pub material: Material,
+}
+
+impl Triangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple) -> Self {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+
+        Self {
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: e2.cross_product(e1).normalize(),
+            transform: Transform::identity(),
+            material: Material::new(),
+        }
+    }
+
+    pub fn p1(&self) -> Tuple {
+        self.p1
+    }
+
+    pub fn p2(&self) -> Tuple {
+        self.p2
+    }
+
+    pub fn p3(&self) -> Tuple {
+        self.p3
+    }
+
+    pub fn normal(&self) -> Tuple {
+        self.normal
+    }
+
+    // returns t along with the barycentric u and v of the hit
+    fn moller_trumbore(&self, ray: &Ray) -> Option<(f64, f64, f64)> {
+        let dir_cross_e2 = ray.direction.cross_product(self.e2);
+        let det = self.e1.dot_product(dir_cross_e2);
+
+        if det.abs() < EPSILON {
+            return None;
+        }
+
+        let f = 1.0 / det;
+        let p1_to_origin = ray.origin - self.p1;
+        let u = f * p1_to_origin.dot_product(dir_cross_e2);
+
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let origin_cross_e1 = p1_to_origin.cross_product(self.e1);
+        let v = f * ray.direction.dot_product(origin_cross_e1);
+
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        Some((f * self.e2.dot_product(origin_cross_e1), u, v))
+    }
+}
+
+impl Shape for Triangle {
+    fn transform(&self) -> &Matrix<4> {
+        self.transform.matrix()
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.transform = Transform::new(transform);
+    }
+
+    fn inverse(&self) -> Option<Matrix<4>> {
+        self.transform.inverse()
+    }
+
+    fn inverse_transpose(&self) -> Option<Matrix<4>> {
+        self.transform.inverse_transpose()
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        match self.moller_trumbore(ray) {
+            Some((t, u, v)) => Intersections(vec![Intersection::with_uv(t, self, u, v)]),
+            None => Intersections::empty(),
+        }
+    }
+
+    fn local_normal_at(&self, _point: &Tuple) -> Tuple {
+        self.normal
+    }
+
+    fn bounds(&self) -> AABB {
+        let mut bounds = AABB::empty();
+        bounds.add_point(&self.p1);
+        bounds.add_point(&self.p2);
+        bounds.add_point(&self.p3);
+        bounds
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmoothTriangle {
+    triangle: Triangle,
+    pub n1: Tuple,
+    pub n2: Tuple,
+    pub n3: Tuple,
+}
+
+impl SmoothTriangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple, n1: Tuple, n2: Tuple, n3: Tuple) -> Self {
+        Self {
+            triangle: Triangle::new(p1, p2, p3),
+            n1,
+            n2,
+            n3,
+        }
+    }
+
+    pub fn triangle(&self) -> &Triangle {
+        &self.triangle
+    }
+}
+
+impl Shape for SmoothTriangle {
+    fn transform(&self) -> &Matrix<4> {
+        self.triangle.transform()
+    }
+
+    fn set_transform(&mut self, transform: Matrix<4>) {
+        self.triangle.set_transform(transform);
+    }
+
+    fn inverse(&self) -> Option<Matrix<4>> {
+        self.triangle.inverse()
+    }
+
+    fn inverse_transpose(&self) -> Option<Matrix<4>> {
+        self.triangle.inverse_transpose()
+    }
+
+    fn material(&self) -> &Material {
+        self.triangle.material()
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        self.triangle.material_mut()
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Intersections<'_> {
+        match self.triangle.moller_trumbore(ray) {
+            Some((t, u, v)) => Intersections(vec![Intersection::with_uv(t, self, u, v)]),
+            None => Intersections::empty(),
+        }
+    }
+
+    // without a u/v there's nothing to interpolate, so fall back to the face normal
+    fn local_normal_at(&self, point: &Tuple) -> Tuple {
+        self.triangle.local_normal_at(point)
+    }
+
+    fn local_normal_at_hit(&self, point: &Tuple, hit: &Intersection) -> Tuple {
+        match hit.uv {
+            Some((u, v)) => self.n2 * u + self.n3 * v + self.n1 * (1.0 - u - v),
+            None => self.local_normal_at(point),
+        }
+    }
+
+    fn bounds(&self) -> AABB {
+        self.triangle.bounds()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn triangle() -> Triangle {
+        Triangle::new(point!(0, 1, 0), point!(-1, 0, 0), point!(1, 0, 0))
+    }
+
+    #[test]
+    fn test_constructor() {
+        let triangle = triangle();
+
+        assert_eq!(vector!(-1, -1, 0), triangle.e1);
+        assert_eq!(vector!(1, -1, 0), triangle.e2);
+        assert_eq!(vector!(0, 0, -1), triangle.normal());
+    }
+
+    #[test]
+    fn test_normal_at() {
+        let triangle = triangle();
+
+        for point in [
+            point!(0, 0.5, 0),
+            point!(-0.5, 0.75, 0),
+            point!(0.5, 0.25, 0),
+        ] {
+            assert_eq!(triangle.normal(), triangle.local_normal_at(&point));
+        }
+    }
+
+    #[test]
+    fn test_intersect_miss() {
+        let triangle = triangle();
+
+        let cases = [
+            // parallel
+            (point!(0, -1, -2), vector!(0, 1, 0)),
+            // past the p1-p3 edge
+            (point!(1, 1, -2), vector!(0, 0, 1)),
+            // past the p1-p2 edge
+            (point!(-1, 1, -2), vector!(0, 0, 1)),
+            // past the p2-p3 edge
+            (point!(0, -1, -2), vector!(0, 0, 1)),
+        ];
+
+        for (origin, direction) in cases {
+            let ray = Ray::new(origin, direction);
+            assert_eq!(0, triangle.local_intersect(&ray).count());
+        }
+    }
+
+    #[test]
+    fn test_intersect() {
+        let triangle = triangle();
+        let ray = Ray::new(point!(0, 0.5, -2), vector!(0, 0, 1));
+
+        assert_eq!(
+            Intersections::from(&[2.0], &triangle),
+            triangle.local_intersect(&ray)
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        let triangle = Triangle::new(point!(-3, 7, 2), point!(6, 2, -4), point!(2, -1, -1));
+
+        assert_eq!(
+            AABB::new(point!(-3, -1, -4), point!(6, 7, 2)),
+            triangle.bounds()
+        );
+    }
+
+    fn smooth_triangle() -> SmoothTriangle {
+        SmoothTriangle::new(
+            point!(0, 1, 0),
+            point!(-1, 0, 0),
+            point!(1, 0, 0),
+            vector!(0, 1, 0),
+            vector!(-1, 0, 0),
+            vector!(1, 0, 0),
+        )
+    }
+
+    #[test]
+    fn test_smooth_intersect_uv() {
+        let triangle = smooth_triangle();
+        let ray = Ray::new(point!(-0.2, 0.3, -2), vector!(0, 0, 1));
+        let xs = triangle.local_intersect(&ray);
+        let (u, v) = xs.0[0].uv.unwrap();
+
+        assert!((u - 0.45).abs() < EPSILON);
+        assert!((v - 0.25).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_smooth_normal_at_hit() {
+        let triangle = smooth_triangle();
+        let hit = Intersection::with_uv(1.0, &triangle, 0.45, 0.25);
+
+        assert_eq!(
+            vector!(-0.5547, 0.83205, 0),
+            triangle.normal_at_hit(&point!(0, 0, 0), &hit)
+        );
+
+        let ray = Ray::new(point!(-0.2, 0.3, -2), vector!(0, 0, 1));
+        let xs = Intersections(vec![hit]);
+        let comps = hit.prepare_computations(&ray, &xs);
+        assert_eq!(vector!(-0.5547, 0.83205, 0), comps.normalv);
+    }
+}
@@ -2,35 +2,30 @@ use crate::{constants::EPSILON, transformations::*};
 use std::ops;
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tuple(pub f64, pub f64, pub f64, pub f64);
 
 #[macro_export]
 macro_rules! point {
   ($x:expr , $y:expr , $z:expr) => {
-    Tuple::new($x as f64, $y as f64, $z as f64, 1.0)
+    $crate::tuples::Tuple::new($x as f64, $y as f64, $z as f64, 1.0)
   };
 }
 
-pub(crate) use point;
-
 #[macro_export]
 macro_rules! vector {
   ($x:expr , $y:expr , $z:expr) => {
-    Tuple::new($x as f64, $y as f64, $z as f64, 0.0)
+    $crate::tuples::Tuple::new($x as f64, $y as f64, $z as f64, 0.0)
   };
 }
 
-pub(crate) use vector;
-
 #[macro_export]
 macro_rules! color {
   ($x:expr , $y:expr , $z:expr) => {
-    Tuple::new($x as f64, $y as f64, $z as f64, 0.0)
+    $crate::tuples::Tuple::new($x as f64, $y as f64, $z as f64, 0.0)
   };
 }
 
-pub(crate) use color;
-
 impl Tuple {
   pub const fn new(x: f64, y: f64, z: f64, w: f64) -> Self {
     Self(x, y, z, w)
@@ -40,6 +35,30 @@ impl Tuple {
     Self::new(x, y, z, w)
   }
 
+  pub const fn x(&self) -> f64 {
+    self.0
+  }
+
+  pub const fn y(&self) -> f64 {
+    self.1
+  }
+
+  pub const fn z(&self) -> f64 {
+    self.2
+  }
+
+  pub const fn w(&self) -> f64 {
+    self.3
+  }
+
+  pub const fn xy(&self) -> [f64; 2] {
+    [self.0, self.1]
+  }
+
+  pub const fn xyz(&self) -> [f64; 3] {
+    [self.0, self.1, self.2]
+  }
+
   pub fn as_color(self) -> (f64, f64, f64) {
     let Tuple(r, g, b, kind) = self;
 
@@ -48,11 +67,11 @@ impl Tuple {
     (r, g, b)
   }
 
-  fn is_vector(&self) -> bool {
+  pub fn is_vector(&self) -> bool {
     self.3 == 0.0
   }
 
-  fn is_point(&self) -> bool {
+  pub fn is_point(&self) -> bool {
     self.3 == 1.0
   }
 
@@ -131,6 +150,24 @@ impl Tuple {
   pub fn reflect(&self, normal: Self) -> Self {
     *self - normal * 2.0 * self.dot_product(normal)
   }
+
+  pub fn reflect_refract(&self, normal: Self, n1: f64, n2: f64) -> (Self, Option<Self>) {
+    let eyev = -*self;
+    let n_ratio = n1 / n2;
+    let cos_i = eyev.dot_product(normal);
+    let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+
+    if sin2_t > 1.0 {
+      return (self.reflect(normal), None);
+    }
+
+    let cos_t = (1.0 - sin2_t).sqrt();
+
+    (
+      self.reflect(normal),
+      Some(normal * (n_ratio * cos_i - cos_t) - eyev * n_ratio),
+    )
+  }
 }
 
 impl ops::Add<Self> for Tuple {
@@ -196,9 +233,28 @@ impl ops::Div<f64> for Tuple {
   }
 }
 
+// weights of p relative to the vertices a, b and c of a triangle, assuming p lies in its plane
+pub fn barycentric(p: &Tuple, a: &Tuple, b: &Tuple, c: &Tuple) -> (f64, f64, f64) {
+  let v0 = *b - *a;
+  let v1 = *c - *a;
+  let v2 = *p - *a;
+
+  let d00 = v0.dot_product(v0);
+  let d01 = v0.dot_product(v1);
+  let d11 = v1.dot_product(v1);
+  let d20 = v2.dot_product(v0);
+  let d21 = v2.dot_product(v1);
+
+  let denominator = d00 * d11 - d01 * d01;
+  let beta = (d11 * d20 - d01 * d21) / denominator;
+  let gamma = (d00 * d21 - d01 * d20) / denominator;
+
+  (1.0 - beta - gamma, beta, gamma)
+}
+
 #[cfg(test)]
 mod test {
-  use super::*;
+  use crate::constants::EPSILON;
 
   #[test]
   fn test_point() {
@@ -222,6 +278,18 @@ mod test {
     assert!(point.is_vector())
   }
 
+  #[test]
+  fn test_swizzle() {
+    let point = point!(1, 2, 3);
+    assert_eq!(1.0, point.x());
+    assert_eq!(2.0, point.y());
+    assert_eq!(3.0, point.z());
+    assert_eq!(1.0, point.w());
+    assert_eq!([1.0, 2.0], point.xy());
+    assert_eq!([1.0, 2.0, 3.0], point.xyz());
+    assert_eq!(0.0, vector!(1, 2, 3).w());
+  }
+
   #[test]
   fn test_add() {
     let a1 = vector!(3.0, -2.0, 5.0);
@@ -324,4 +392,52 @@ mod test {
     let normal = vector!(2f64.sqrt() / 2.0, 2f64.sqrt() / 2.0, 0);
     assert_eq!(vector!(1, 0, 0), vector.reflect(normal));
   }
+
+  #[test]
+  fn test_reflect_refract() {
+    let normal = vector!(0, 0, -1);
+
+    let (reflected, refracted) = vector!(0, 0, 1).reflect_refract(normal, 1.0, 1.5);
+    assert_eq!(vector!(0, 0, -1), reflected);
+    assert_eq!(Some(vector!(0, 0, 1)), refracted);
+
+    let incoming = vector!(0, 2f64.sqrt() / 2.0, 2f64.sqrt() / 2.0);
+    let (reflected, refracted) = incoming.reflect_refract(normal, 1.0, 1.5);
+    assert_eq!(vector!(0, 2f64.sqrt() / 2.0, -2f64.sqrt() / 2.0), reflected);
+    let refracted = refracted.unwrap();
+    let sin_t = refracted.1 / refracted.magnitude();
+    assert!((1.0 * (2f64.sqrt() / 2.0) - 1.5 * sin_t).abs() < EPSILON);
+    assert!((1.0 - refracted.magnitude()).abs() < EPSILON);
+    assert!(refracted.2 > 0.0);
+
+    let (reflected, refracted) = incoming.reflect_refract(normal, 1.5, 1.0);
+    assert_eq!(vector!(0, 2f64.sqrt() / 2.0, -2f64.sqrt() / 2.0), reflected);
+    assert_eq!(None, refracted);
+  }
+
+  #[test]
+  fn test_barycentric() {
+    use super::barycentric;
+
+    let a = point!(0, 1, 0);
+    let b = point!(-1, 0, 0);
+    let c = point!(1, 0, 0);
+
+    assert_eq!((1.0, 0.0, 0.0), barycentric(&a, &a, &b, &c));
+    assert_eq!((0.0, 1.0, 0.0), barycentric(&b, &a, &b, &c));
+    assert_eq!((0.0, 0.0, 1.0), barycentric(&c, &a, &b, &c));
+
+    let centroid = point!(0, 1.0 / 3.0, 0);
+    let (alpha, beta, gamma) = barycentric(&centroid, &a, &b, &c);
+    assert!((alpha - 1.0 / 3.0).abs() < EPSILON);
+    assert!((beta - 1.0 / 3.0).abs() < EPSILON);
+    assert!((gamma - 1.0 / 3.0).abs() < EPSILON);
+
+    let (alpha, beta, gamma) = barycentric(&point!(0.2, 0.3, 0), &a, &b, &c);
+    assert!((alpha + beta + gamma - 1.0).abs() < EPSILON);
+    assert_eq!(
+      vector!(0.2, 0.3, 0),
+      (a - c) * alpha + (b - c) * beta + (c - point!(0, 0, 0))
+    );
+  }
 }
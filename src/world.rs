@@ -0,0 +1,804 @@
+use crate::{
+    camera::Camera,
+    canvas::Canvas,
+    constants::EPSILON,
+    intersections::{schlick, Computations, Intersections},
+    lights::{Light, PointLight},
+    materials::Material,
+    rays::Ray,
+    shapes::Shape,
+    spheres::Sphere,
+    transformations::scaling,
+    tuples::Tuple,
+};
+use std::f64::consts::PI;
+
+const MAX_DEPTH: usize = 5;
+const GLOSSY_SAMPLES: usize = 16;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SceneWarning {
+    NoLights,
+    NanTransform(usize),
+    SingularTransform(usize),
+    EnergyGain(usize),
+    OutsideView(usize),
+}
+
+pub struct World {
+    pub objects: Vec<Box<dyn Shape>>,
+    pub lights: Vec<Light>,
+    pub ambient_env: Option<Canvas>,
+}
+
+impl World {
+    pub const fn new() -> Self {
+        Self {
+            objects: Vec::new(),
+            lights: Vec::new(),
+            ambient_env: None,
+        }
+    }
+
+    pub fn intersect(&self, ray: &Ray) -> Intersections<'_> {
+        let mut intersections: Vec<_> = self
+            .objects
+            .iter()
+            .flat_map(|object| object.intersect(ray))
+            .collect();
+
+        intersections.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+        Intersections(intersections)
+    }
+
+    pub fn color_at(&self, ray: &Ray) -> Tuple {
+        self.color_at_depth(ray, MAX_DEPTH)
+    }
+
+    pub fn color_at_depth(&self, ray: &Ray, remaining: usize) -> Tuple {
+        let intersections = self.intersect(ray);
+
+        match intersections.hit() {
+            Some(hit) => self.shade_hit(&hit.prepare_computations(ray, &intersections), remaining),
+            None => color!(0, 0, 0),
+        }
+    }
+
+    pub fn shade_hit(&self, comps: &Computations, remaining: usize) -> Tuple {
+        let material = comps.object.material();
+
+        // an environment map replaces the flat per-light ambient term
+        let ambient = match &self.ambient_env {
+            Some(env) => {
+                material
+                    .color_at(comps.object, &comps.over_point)
+                    .hadamard_product(env.sample_equirectangular(&comps.normalv))
+                    * material.ambient
+            }
+            None => color!(0, 0, 0),
+        };
+
+        let lighting = match self.ambient_env {
+            Some(_) => Material::direct_lighting,
+            None => Material::lighting,
+        };
+
+        let surface = self
+            .lights
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| material.is_linked_to(*index))
+            .fold(ambient, |acc, (_, light)| {
+                let intensity = light.intensity_at(&comps.over_point, self);
+
+                acc + lighting(
+                    material,
+                    comps.object,
+                    light,
+                    &comps.over_point,
+                    &comps.eyev,
+                    &comps.normalv,
+                    intensity,
+                )
+            });
+
+        let reflected = self.reflected_color(comps, remaining);
+        let refracted = self.refracted_color(comps, remaining);
+
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = schlick(comps);
+            surface + reflected * reflectance + refracted * (1.0 - reflectance)
+        } else {
+            surface + reflected + refracted
+        }
+    }
+
+    pub fn reflected_color(&self, comps: &Computations, remaining: usize) -> Tuple {
+        let material = comps.object.material();
+
+        if remaining == 0 || material.reflective == 0.0 {
+            return color!(0, 0, 0);
+        }
+
+        if material.reflection_roughness == 0.0 {
+            let ray = Ray::new(comps.over_point, comps.reflectv);
+            return self
+                .color_at_depth(&ray, remaining - 1)
+                .hadamard_product(material.reflection_tint)
+                * material.reflective;
+        }
+
+        // spread the samples over a disc around the mirror direction in a golden-angle spiral
+        let reflectv = comps.reflectv;
+        let helper = if reflectv.0.abs() < 0.9 {
+            vector!(1, 0, 0)
+        } else {
+            vector!(0, 1, 0)
+        };
+        let u = reflectv.cross_product(helper).normalize();
+        let v = reflectv.cross_product(u);
+        let golden_angle = PI * (3.0 - 5f64.sqrt());
+
+        let total = (0..GLOSSY_SAMPLES).fold(color!(0, 0, 0), |acc, i| {
+            let radius =
+                material.reflection_roughness * ((i as f64 + 0.5) / GLOSSY_SAMPLES as f64).sqrt();
+            let theta = i as f64 * golden_angle;
+            let direction = (reflectv + (u * theta.cos() + v * theta.sin()) * radius).normalize();
+
+            acc + self.color_at_depth(&Ray::new(comps.over_point, direction), remaining - 1)
+        });
+
+        total.hadamard_product(material.reflection_tint)
+            * (material.reflective / GLOSSY_SAMPLES as f64)
+    }
+
+    pub fn refracted_color(&self, comps: &Computations, remaining: usize) -> Tuple {
+        let transparency = comps.object.material().transparency;
+
+        if remaining == 0 || transparency == 0.0 {
+            return color!(0, 0, 0);
+        }
+
+        let n_ratio = comps.n1 / comps.n2;
+        let cos_i = comps.eyev.dot_product(comps.normalv);
+        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+
+        // total internal reflection
+        if sin2_t > 1.0 {
+            return color!(0, 0, 0);
+        }
+
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
+        let ray = Ray::new(comps.under_point, direction);
+
+        self.color_at_depth(&ray, remaining - 1) * transparency
+    }
+
+    pub fn validate(&self) -> Vec<SceneWarning> {
+        let mut warnings = Vec::new();
+
+        if self.lights.is_empty() {
+            warnings.push(SceneWarning::NoLights);
+        }
+
+        for (index, object) in self.objects.iter().enumerate() {
+            let transform = object.transform();
+
+            if (0..4).any(|row| transform[row].iter().any(|value| value.is_nan())) {
+                warnings.push(SceneWarning::NanTransform(index));
+            } else if object.inverse().is_none() {
+                warnings.push(SceneWarning::SingularTransform(index));
+            }
+
+            // light leaving the surface through diffusion, reflection and refraction combined
+            let material = object.material();
+            if material.diffuse + material.reflective + material.transparency > 1.0 + EPSILON {
+                warnings.push(SceneWarning::EnergyGain(index));
+            }
+        }
+
+        warnings
+    }
+
+    // validate, plus a warning for each object the camera can't possibly see
+    pub fn validate_with(&self, camera: &Camera) -> Vec<SceneWarning> {
+        let mut warnings = self.validate();
+
+        for (index, object) in self.objects.iter().enumerate() {
+            if !camera.sees(&object.bounds().transform(object.transform())) {
+                warnings.push(SceneWarning::OutsideView(index));
+            }
+        }
+
+        warnings
+    }
+
+    // whether anything sits between point and a position on a light
+    pub fn is_shadowed(&self, point: &Tuple, light_position: &Tuple) -> bool {
+        let v = *light_position - *point;
+        let distance = v.magnitude();
+        let ray = Ray::new(*point, v.normalize());
+
+        match self.intersect(&ray).hit() {
+            Some(hit) => hit.t < distance,
+            None => false,
+        }
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        let light = PointLight::new(point!(-10, 10, -10), color!(1, 1, 1));
+
+        let mut s1 = Sphere::new();
+        s1.material.color = color!(0.8, 1.0, 0.6);
+        s1.material.diffuse = 0.7;
+        s1.material.specular = 0.2;
+
+        let mut s2 = Sphere::new();
+        s2.set_transform(scaling(0.5, 0.5, 0.5));
+
+        Self {
+            objects: vec![Box::new(s1), Box::new(s2)],
+            lights: vec![Light::from(light)],
+            ambient_env: None,
+        }
+    }
+}
+
+// objects are trait objects, so they are saved by concrete type; only spheres are supported
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+enum SceneShape<'a> {
+    Sphere(std::borrow::Cow<'a, Sphere>),
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct WorldData<'a> {
+    objects: Vec<SceneShape<'a>>,
+    lights: std::borrow::Cow<'a, [Light]>,
+    ambient_env: Option<std::borrow::Cow<'a, Canvas>>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for World {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+        use std::{any::Any, borrow::Cow};
+
+        let objects = self
+            .objects
+            .iter()
+            .map(|object| {
+                let sphere = (object.as_ref() as &dyn Any)
+                    .downcast_ref()
+                    .ok_or_else(|| S::Error::custom("only spheres can be serialized"))?;
+                Ok(SceneShape::Sphere(Cow::Borrowed(sphere)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        WorldData {
+            objects,
+            lights: Cow::Borrowed(&self.lights),
+            ambient_env: self.ambient_env.as_ref().map(Cow::Borrowed),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for World {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = WorldData::deserialize(deserializer)?;
+
+        Ok(World {
+            objects: data
+                .objects
+                .into_iter()
+                .map(|object| match object {
+                    SceneShape::Sphere(sphere) => Box::new(sphere.into_owned()) as Box<dyn Shape>,
+                })
+                .collect(),
+            lights: data.lights.into_owned(),
+            ambient_env: data.ambient_env.map(|env| env.into_owned()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transformations::{translation, view_transform};
+    use crate::{cubes::Cube, lights::AreaLight, planes::Plane};
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    #[test]
+    fn test_constructor() {
+        let world = World::new();
+
+        assert!(world.objects.is_empty());
+        assert!(world.lights.is_empty());
+        assert!(world.ambient_env.is_none());
+    }
+
+    #[test]
+    fn test_default() {
+        let world = World::default();
+
+        let light = PointLight::new(point!(-10, 10, -10), color!(1, 1, 1));
+        assert_eq!(vec![Light::from(light)], world.lights);
+
+        assert_eq!(2, world.objects.len());
+        assert_eq!(color!(0.8, 1.0, 0.6), world.objects[0].material().color);
+        assert_eq!(0.7, world.objects[0].material().diffuse);
+        assert_eq!(0.2, world.objects[0].material().specular);
+        assert_eq!(
+            Intersections::from(&[-0.5, 0.5], world.objects[1].as_ref()),
+            world.objects[1].intersect(&Ray::new(point!(0, 0, 0), vector!(0, 0, 1)))
+        );
+    }
+
+    #[test]
+    fn test_intersect() {
+        let world = World::default();
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+
+        let xs = world.intersect(&ray);
+
+        assert_eq!(4, xs.count());
+        assert_eq!(4.0, xs.0[0].t);
+        assert_eq!(4.5, xs.0[1].t);
+        assert_eq!(5.5, xs.0[2].t);
+        assert_eq!(6.0, xs.0[3].t);
+    }
+
+    #[test]
+    fn test_color_at() {
+        let world = World::default();
+
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 1, 0));
+        assert_eq!(color!(0, 0, 0), world.color_at(&ray));
+
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+        assert_eq!(color!(0.38066, 0.47583, 0.2855), world.color_at(&ray));
+
+        let mut world = World::default();
+        world.objects[0].material_mut().ambient = 1.0;
+        world.objects[1].material_mut().ambient = 1.0;
+        let ray = Ray::new(point!(0, 0, 0.75), vector!(0, 0, -1));
+        assert_eq!(world.objects[1].material().color, world.color_at(&ray));
+    }
+
+    #[test]
+    fn test_is_shadowed() {
+        let world = World::default();
+        let light = world.lights[0].position();
+
+        assert!(!world.is_shadowed(&point!(0, 10, 0), &light));
+        assert!(world.is_shadowed(&point!(10, -10, 10), &light));
+        assert!(!world.is_shadowed(&point!(-20, 20, -20), &light));
+        assert!(!world.is_shadowed(&point!(-2, 2, -2), &light));
+    }
+
+    #[test]
+    fn test_color_at_in_shadow() {
+        let mut world = World::new();
+        world.lights.push(Light::from(PointLight::new(
+            point!(0, 0, -10),
+            color!(1, 1, 1),
+        )));
+        world.objects.push(Box::new(Sphere::new()));
+        let mut sphere = Sphere::new();
+        sphere.set_transform(translation(0.0, 0.0, 10.0));
+        world.objects.push(Box::new(sphere));
+
+        let ray = Ray::new(point!(0, 0, 5), vector!(0, 0, 1));
+        assert_eq!(color!(0.1, 0.1, 0.1), world.color_at(&ray));
+    }
+
+    #[test]
+    fn test_color_at_mixed_lights() {
+        let point_light = PointLight::new(point!(-10, 10, -10), color!(1, 1, 1));
+        // a single cell centred on the point light
+        let area_light = AreaLight::new(
+            point!(-10.5, 10, -10.5),
+            vector!(1, 0, 0),
+            1,
+            vector!(0, 0, 1),
+            1,
+            color!(1, 1, 1),
+        );
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+
+        let mut world = World::default();
+        let point_color = world.color_at(&ray);
+
+        world.lights = vec![Light::from(area_light)];
+        assert_eq!(point_color, world.color_at(&ray));
+
+        world.lights = vec![Light::from(point_light), Light::from(area_light)];
+        let mixed_color = world.color_at(&ray);
+        assert_eq!(point_color * 2.0, mixed_color);
+    }
+
+    #[test]
+    fn test_color_at_light_linking() {
+        let mut world = World::new();
+        world.lights.push(Light::from(PointLight::new(
+            point!(-10, 10, -10),
+            color!(1, 1, 1),
+        )));
+        world.lights.push(Light::from(PointLight::new(
+            point!(10, 10, -10),
+            color!(1, 1, 1),
+        )));
+
+        let mut linked = Sphere::new();
+        linked.set_transform(translation(-2.0, 0.0, 0.0));
+        let mut excluded = Sphere::new();
+        excluded.set_transform(translation(2.0, 0.0, 0.0));
+        excluded.material.light_mask = Some(0b01);
+        world.objects.push(Box::new(linked));
+        world.objects.push(Box::new(excluded.clone()));
+
+        let linked_color = world.color_at(&Ray::new(point!(-2, 0, -5), vector!(0, 0, 1)));
+        let excluded_color = world.color_at(&Ray::new(point!(2, 0, -5), vector!(0, 0, 1)));
+
+        assert!(excluded_color.0 < linked_color.0);
+
+        excluded.material.light_mask = None;
+        world.objects[1] = Box::new(excluded);
+        assert_eq!(
+            linked_color,
+            world.color_at(&Ray::new(point!(2, 0, -5), vector!(0, 0, 1)))
+        );
+    }
+
+    #[test]
+    fn test_color_at_ambient_env() {
+        // bright sky above the horizon, dark ground below it
+        let mut env = Canvas::new(8, 4);
+        for y in 0..2 {
+            for x in 0..8 {
+                env.write_pixel(x, y, &color!(1, 1, 1));
+            }
+        }
+
+        let mut world = World::new();
+        world.ambient_env = Some(env);
+        let mut floor = Sphere::new();
+        floor.material.ambient = 1.0;
+        floor.material.diffuse = 0.0;
+        floor.material.specular = 0.0;
+        world.objects.push(Box::new(floor));
+
+        let facing_up = world.color_at(&Ray::new(point!(0, 5, 0), vector!(0, -1, 0)));
+        let facing_down = world.color_at(&Ray::new(point!(0, -5, 0), vector!(0, 1, 0)));
+
+        assert_eq!(color!(1, 1, 1), facing_up);
+        assert_eq!(color!(0, 0, 0), facing_down);
+        assert!(facing_up.0 > facing_down.0);
+    }
+
+    #[test]
+    fn test_reflected_color() {
+        let mut world = World::default();
+        world.objects[1].material_mut().ambient = 1.0;
+        let ray = Ray::new(point!(0, 0, 0), vector!(0, 0, 1));
+        let xs = Intersections::from(&[1.0], world.objects[1].as_ref());
+        let comps = xs.0[0].prepare_computations(&ray, &xs);
+        assert_eq!(color!(0, 0, 0), world.reflected_color(&comps, MAX_DEPTH));
+
+        let mut world = World::default();
+        let mut plane = Plane::new();
+        plane.material.reflective = 0.5;
+        plane.set_transform(translation(0.0, -1.0, 0.0));
+        world.objects.push(Box::new(plane));
+        let ray = Ray::new(point!(0, 0, -3), vector!(0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2));
+        let xs = Intersections::from(&[2f64.sqrt()], world.objects[2].as_ref());
+        let comps = xs.0[0].prepare_computations(&ray, &xs);
+        assert_eq!(
+            color!(0.19033, 0.23791, 0.14274),
+            world.reflected_color(&comps, MAX_DEPTH)
+        );
+        assert_eq!(
+            color!(0.87675, 0.92434, 0.82917),
+            world.shade_hit(&comps, MAX_DEPTH)
+        );
+        assert_eq!(color!(0, 0, 0), world.reflected_color(&comps, 0));
+    }
+
+    #[test]
+    fn test_reflected_color_metal() {
+        let reflection = |material: Material| {
+            let mut world = World::default();
+            let mut plane = Plane::new();
+            plane.material = material;
+            plane.set_transform(translation(0.0, -1.0, 0.0));
+            world.objects.push(Box::new(plane));
+
+            let ray = Ray::new(point!(0, 0, -3), vector!(0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2));
+            let xs = Intersections::from(&[2f64.sqrt()], world.objects[2].as_ref());
+            let comps = xs.0[0].prepare_computations(&ray, &xs);
+            world.reflected_color(&comps, MAX_DEPTH)
+        };
+
+        let gold = color!(1, 0.8, 0.3);
+        let mirror = reflection(Material {
+            reflective: 1.0,
+            ..Material::new()
+        });
+        let metal = reflection(Material::from_pbr(gold, 1.0, 0.0));
+
+        // a fully metallic surface reflects everything, tinted by its base color
+        assert_eq!(mirror.hadamard_product(gold), metal);
+        assert_eq!(
+            color!(0, 0, 0),
+            reflection(Material::from_pbr(gold, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_reflected_color_mirror() {
+        let mut world = World::new();
+        world.lights.push(Light::from(PointLight::new(
+            point!(-10, 10, -10),
+            color!(1, 1, 1),
+        )));
+
+        let mut mirror = Plane::new();
+        mirror.material.color = color!(0, 0, 0);
+        mirror.material.ambient = 0.0;
+        mirror.material.diffuse = 0.0;
+        mirror.material.specular = 0.0;
+        mirror.material.reflective = 1.0;
+        mirror.set_transform(translation(0.0, -1.0, 0.0));
+        world.objects.push(Box::new(mirror));
+
+        let mut ball = Sphere::new();
+        ball.material.color = color!(1, 0, 0);
+        ball.material.specular = 0.0;
+        ball.set_transform(translation(0.0, 1.0, 5.0));
+        world.objects.push(Box::new(ball));
+
+        // looking down at the mirror below the ball sees the ball's reflection
+        let direct = world.color_at(&Ray::new(point!(0, 1, 0), vector!(0, 0, 1)));
+        let reflected = world.color_at(&Ray::new(
+            point!(0, 1, 0),
+            (point!(0, -1, 2.5) - point!(0, 1, 0)).normalize(),
+        ));
+
+        assert!(direct.0 > 0.0 && direct.1 == 0.0);
+        assert!(reflected.0 > 0.0 && reflected.1 == 0.0);
+    }
+
+    #[test]
+    fn test_color_at_parallel_mirrors() {
+        let mut world = World::new();
+        world.lights.push(Light::from(PointLight::new(
+            point!(0, 0, 0),
+            color!(1, 1, 1),
+        )));
+
+        let mut lower = Plane::new();
+        lower.material.reflective = 1.0;
+        lower.set_transform(translation(0.0, -1.0, 0.0));
+        world.objects.push(Box::new(lower));
+
+        let mut upper = Plane::new();
+        upper.material.reflective = 1.0;
+        upper.set_transform(translation(0.0, 1.0, 0.0));
+        world.objects.push(Box::new(upper));
+
+        // terminates instead of bouncing between the mirrors forever
+        let ray = Ray::new(point!(0, 0, 0), vector!(0, 1, 0));
+        let color = world.color_at(&ray);
+        assert!(color.0.is_finite());
+
+        assert_eq!(
+            world.color_at_depth(&ray, 0) * 2.0,
+            world.color_at_depth(&ray, 1)
+        );
+    }
+
+    #[test]
+    fn test_reflected_color_roughness() {
+        let mut world = World::new();
+        world.lights.push(Light::from(PointLight::new(
+            point!(0, 10, -10),
+            color!(1, 1, 1),
+        )));
+
+        let mut mirror = Plane::new();
+        mirror.material.color = color!(0, 0, 0);
+        mirror.material.ambient = 0.0;
+        mirror.material.diffuse = 0.0;
+        mirror.material.specular = 0.0;
+        mirror.material.reflective = 1.0;
+        world.objects.push(Box::new(mirror));
+
+        // a glowing panel covering x < 0, its vertical edge reflected in the mirror
+        let mut panel = Cube::new();
+        panel.material.ambient = 1.0;
+        panel.material.diffuse = 0.0;
+        panel.material.specular = 0.0;
+        panel.set_transform(translation(-5.0, 5.0, 10.0) * scaling(5.0, 5.0, 1.0));
+        world.objects.push(Box::new(panel));
+
+        let look_at = |world: &World, x: f64| {
+            world
+                .color_at(&Ray::new(
+                    point!(x, 1, 0),
+                    (point!(x, 0, 5) - point!(x, 1, 0)).normalize(),
+                ))
+                .0
+        };
+
+        assert_eq!(1.0, look_at(&world, -0.05));
+        assert_eq!(0.0, look_at(&world, 0.05));
+
+        world.objects[0].material_mut().reflection_roughness = 0.2;
+        let left = look_at(&world, -0.05);
+        let right = look_at(&world, 0.05);
+        assert!(0.0 < right && right < left && left < 1.0);
+    }
+
+    #[test]
+    fn test_refracted_color() {
+        let world = World::default();
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+        let xs = Intersections::from(&[4.0, 6.0], world.objects[0].as_ref());
+        let comps = xs.0[0].prepare_computations(&ray, &xs);
+        assert_eq!(color!(0, 0, 0), world.refracted_color(&comps, MAX_DEPTH));
+
+        let mut world = World::default();
+        world.objects[0].material_mut().transparency = 1.0;
+        world.objects[0].material_mut().refractive_index = 1.5;
+        let comps = xs_for(&world, &ray, &[4.0, 6.0], 0);
+        assert_eq!(color!(0, 0, 0), world.refracted_color(&comps, 0));
+
+        let ray = Ray::new(point!(0, 0, FRAC_1_SQRT_2), vector!(0, 1, 0));
+        let comps = xs_for(&world, &ray, &[-FRAC_1_SQRT_2, FRAC_1_SQRT_2], 1);
+        assert_eq!(color!(0, 0, 0), world.refracted_color(&comps, MAX_DEPTH));
+    }
+
+    fn xs_for<'a>(world: &'a World, ray: &Ray, ts: &[f64], index: usize) -> Computations<'a> {
+        let xs = Intersections::from(ts, world.objects[0].as_ref());
+        xs.0[index].prepare_computations(ray, &xs)
+    }
+
+    #[test]
+    fn test_shade_hit_transparent() {
+        let mut world = World::default();
+
+        let mut floor = Plane::new();
+        floor.set_transform(translation(0.0, -1.0, 0.0));
+        floor.material.transparency = 0.5;
+        floor.material.refractive_index = 1.5;
+        world.objects.push(Box::new(floor));
+
+        let mut ball = Sphere::new();
+        ball.material.color = color!(1, 0, 0);
+        ball.material.ambient = 0.5;
+        ball.set_transform(translation(0.0, -3.5, -0.5));
+        world.objects.push(Box::new(ball));
+
+        let ray = Ray::new(point!(0, 0, -3), vector!(0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2));
+        let xs = Intersections::from(&[2f64.sqrt()], world.objects[2].as_ref());
+        let comps = xs.0[0].prepare_computations(&ray, &xs);
+        assert_eq!(
+            color!(0.93642, 0.68642, 0.68642),
+            world.shade_hit(&comps, MAX_DEPTH)
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(World::default().validate().is_empty());
+
+        let mut world = World::new();
+        let mut flat = Sphere::new();
+        flat.set_transform(scaling(0.0, 0.0, 0.0));
+        world.objects.push(Box::new(flat));
+        assert_eq!(
+            vec![SceneWarning::NoLights, SceneWarning::SingularTransform(0)],
+            world.validate()
+        );
+
+        let mut world = World::default();
+        world.objects[0].set_transform(translation(f64::NAN, 0.0, 0.0));
+        world.objects[1].material_mut().reflective = 0.5;
+        assert_eq!(
+            vec![SceneWarning::NanTransform(0), SceneWarning::EnergyGain(1)],
+            world.validate()
+        );
+    }
+
+    #[test]
+    fn test_validate_with() {
+        let mut camera = Camera::new(100, 50, PI / 3.0);
+        camera.set_transform(view_transform(
+            point!(0, 0, -5),
+            point!(0, 0, 0),
+            vector!(0, 1, 0),
+        ));
+
+        let mut world = World::default();
+        assert!(world.validate_with(&camera).is_empty());
+
+        // behind the camera, off to the side, half in frame, and unbounded
+        for transform in [
+            translation(0.0, 0.0, -10.0),
+            translation(20.0, 0.0, 0.0),
+            translation(3.0, 0.0, 0.0),
+        ] {
+            let mut sphere = Sphere::new();
+            sphere.set_transform(transform);
+            world.objects.push(Box::new(sphere));
+        }
+        world.objects.push(Box::new(Plane::new()));
+
+        assert_eq!(
+            vec![SceneWarning::OutsideView(2), SceneWarning::OutsideView(3)],
+            world.validate_with(&camera)
+        );
+    }
+
+    #[test]
+    fn test_shade_hit_schlick() {
+        let mut world = World::default();
+
+        let mut floor = Plane::new();
+        floor.set_transform(translation(0.0, -1.0, 0.0));
+        floor.material.reflective = 0.5;
+        floor.material.transparency = 0.5;
+        floor.material.refractive_index = 1.5;
+        world.objects.push(Box::new(floor));
+
+        let mut ball = Sphere::new();
+        ball.material.color = color!(1, 0, 0);
+        ball.material.ambient = 0.5;
+        ball.set_transform(translation(0.0, -3.5, -0.5));
+        world.objects.push(Box::new(ball));
+
+        let ray = Ray::new(point!(0, 0, -3), vector!(0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2));
+        let xs = Intersections::from(&[2f64.sqrt()], world.objects[2].as_ref());
+        let comps = xs.0[0].prepare_computations(&ray, &xs);
+        assert_eq!(
+            color!(0.93391, 0.69643, 0.69243),
+            world.shade_hit(&comps, MAX_DEPTH)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let world = World::default();
+
+        let json = serde_json::to_string(&world).unwrap();
+        let loaded: World = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(world.objects.len(), loaded.objects.len());
+        assert_eq!(world.lights, loaded.lights);
+        for (object, loaded_object) in world.objects.iter().zip(&loaded.objects) {
+            assert_eq!(object.transform(), loaded_object.transform());
+            assert_eq!(object.inverse(), loaded_object.inverse());
+            assert_eq!(object.material(), loaded_object.material());
+        }
+
+        let ray = Ray::new(point!(0, 0, -5), vector!(0, 0, 1));
+        assert_eq!(world.color_at(&ray), loaded.color_at(&ray));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_unsupported_shape() {
+        let mut world = World::default();
+        world.objects.push(Box::new(Plane::new()));
+
+        assert!(serde_json::to_string(&world).is_err());
+    }
+}